@@ -0,0 +1,59 @@
+//! Example: Add a payee to the contact book & then view it by uid.
+//!
+//! Inputs used in this example:
+//! - user_id
+//! - contact:
+//!   - name
+//!   - address      (payee's wallet address)
+
+use colored::Colorize;
+use unifi_examples::{display_contact, init_sdk, take_input, with_spinner};
+use unifi_sdk_primitives::types::ContactPayload;
+
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+	let sdk = init_sdk();
+	with_spinner(
+		spinoff::spinners::Dots.into(),
+		"🩺 Checking API health...".to_string(),
+		sdk.health_check(),
+		Some("✅ API is healthy!".to_string()),
+		true,
+	)
+	.await
+	.unwrap_or_else(|e| panic!("{}", e.to_string().red().bold()));
+
+	println!("================================================");
+
+	let user_id = &take_input("Enter a valid User ID: ")?;
+	let name = take_input("Enter contact name: ")?;
+	let address = take_input("Enter contact's wallet address: ")?;
+
+	println!("================================================");
+
+	let contacts = with_spinner(
+		spinoff::spinners::Dots.into(),
+		"⏳ Adding contact...".to_string(),
+		sdk.add_user_contacts(user_id, &[ContactPayload { name, address, ..Default::default() }]),
+		Some("✅ Contact added!".to_string()),
+		true,
+	)
+	.await
+	.unwrap_or_else(|e| panic!("{}", e.to_string().red().bold()));
+
+	for contact in contacts.into_iter() {
+		let contact = with_spinner(
+			spinoff::spinners::Dots.into(),
+			format!("⏳ Fetching contact: {}...", contact.uid),
+			sdk.get_user_contact_by_uid(user_id, &contact.uid),
+			Some("✅ Contact:".to_string()),
+			true,
+		)
+		.await
+		.unwrap_or_else(|e| panic!("{}", e.to_string().red().bold()));
+
+		display_contact(contact);
+	}
+
+	Ok(())
+}
@@ -0,0 +1,58 @@
+//! Example: View the contact book & search contacts by name.
+
+use colored::Colorize;
+use unifi_examples::{display_contact, init_sdk, take_input, with_spinner};
+
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+	let sdk = init_sdk();
+	with_spinner(
+		spinoff::spinners::Dots.into(),
+		"🩺 Checking API health...".to_string(),
+		sdk.health_check(),
+		Some("✅ API is healthy!".to_string()),
+		true,
+	)
+	.await
+	.unwrap_or_else(|e| panic!("{}", e.to_string().red().bold()));
+
+	println!("================================================");
+
+	let user_id = &take_input("Enter a valid User ID: ")?;
+
+	println!("================================================");
+
+	let contacts = with_spinner(
+		spinoff::spinners::Dots.into(),
+		"⏳ Loading contacts...".to_string(),
+		sdk.get_user_contacts(user_id),
+		Some("✅ Contacts:".to_string()),
+		true,
+	)
+	.await
+	.unwrap_or_else(|e| panic!("{}", e.to_string().red().bold()));
+
+	println!("{}", format!("Total contacts: {}", contacts.len()).bold().blue());
+	for contact in contacts.into_iter() {
+		display_contact(contact);
+	}
+
+	println!("================================================");
+
+	let name = &take_input("Search contacts by name: ")?;
+	let contacts = with_spinner(
+		spinoff::spinners::Dots.into(),
+		format!("⏳ Searching contacts named '{name}'..."),
+		sdk.get_user_contacts_by_name(user_id, name),
+		Some("✅ Matched contacts:".to_string()),
+		true,
+	)
+	.await
+	.unwrap_or_else(|e| panic!("{}", e.to_string().red().bold()));
+
+	for contact in contacts.into_iter() {
+		display_contact(contact);
+	}
+
+	Ok(())
+}
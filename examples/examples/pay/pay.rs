@@ -13,11 +13,15 @@
 //!   - amount       (token amount to send)
 
 use colored::Colorize;
-use unifi_examples::{init_sdk, take_input, with_spinner};
+use unifi_examples::{display_pay_receipt, init_sdk, take_input, with_spinner};
 use unifi_sdk_primitives::{
-	types::{ChainName, PayOnchainPayload, PreOcpPayload, PreOcpValues, StableCoin},
-	utils::validate_and_parse_amount,
+	amount::Amount,
+	types::{
+		ChainName, OcPayReceiptStatus, PayOnchainPayload, PreOcpPayload, PreOcpValues, StableCoin,
+	},
+	utils::validate_amount,
 };
+use unifi_sdk_rs::types::WaitForReceiptOpts;
 
 #[tokio::main]
 async fn main() -> eyre::Result<()> {
@@ -71,9 +75,10 @@ async fn main() -> eyre::Result<()> {
 				.bold()
 		);
 	}
-	if validate_and_parse_amount(amount, selected_coin, &net_balance, &est_fee, is_fee_incl)
-		.is_err()
-	{
+	let balance = Amount::parse(&net_balance, selected_coin, selected_chain)?;
+	let fee = Amount::parse(&est_fee, selected_coin, selected_chain)?;
+	let amount_to_pay = Amount::parse(amount, selected_coin, selected_chain)?;
+	if validate_amount(&amount_to_pay, &balance, &fee, is_fee_incl).is_err() {
 		println!(
 			"{}",
 			"Insufficient balance 💰.\nPlease 📩 deposit or request faucet (on Sepolia testnet)"
@@ -83,20 +88,19 @@ async fn main() -> eyre::Result<()> {
 	}
 
 	// ==================== Submit payment ==================================
+	let payload = PayOnchainPayload {
+		chain: selected_chain,
+		coin: selected_coin,
+		to_address: to_address.to_owned(),
+		amount: amount.to_owned(),
+		memo: unifi_sdk_primitives::types::Memo::General,
+	};
+	// Same payment details => same key. So, re-running this example doesn't pay twice.
+	let idempotency_key = payload.idempotency_key(user_id);
 	let receipt_id = with_spinner(
 		spinoff::spinners::Dots.into(),
 		"🚀 Processing payment ⏳...".to_string(),
-		sdk.pay_onchain(
-			user_id,
-			is_fee_incl,
-			PayOnchainPayload {
-				chain: selected_chain,
-				coin: selected_coin,
-				to_address: to_address.to_owned(),
-				amount: amount.to_owned(),
-				memo: unifi_sdk_primitives::types::Memo::General,
-			},
-		),
+		sdk.pay_onchain_with_idempotency_key(user_id, is_fee_incl, payload, &idempotency_key),
 		Some("✅ Payment submitted!".to_string()),
 		true,
 	)
@@ -106,5 +110,22 @@ async fn main() -> eyre::Result<()> {
 	println!("{}", format!("🧾 Receipt ID: {}", receipt_id).green().bold());
 	println!("{}", "🚚 Track this payment using the receipt ID above 👆.".cyan());
 
+	// ==================== Wait for confirmation ============================
+	let receipt = with_spinner(
+		spinoff::spinners::Dots.into(),
+		"⛓️ Waiting for onchain confirmation ⏳...".to_string(),
+		sdk.wait_for_receipt(
+			&receipt_id,
+			OcPayReceiptStatus::Confirmed,
+			WaitForReceiptOpts::default(),
+		),
+		Some("✅ Payment confirmed!".to_string()),
+		true,
+	)
+	.await
+	.unwrap_or_else(|e| panic!("{}", e.to_string().red().bold()));
+
+	display_pay_receipt(receipt);
+
 	Ok(())
 }
@@ -5,14 +5,17 @@
 //! NOTE: During each full example run, the pointer is brought back from the last page to first
 //! page.
 //!
+//! Pages are followed automatically by `Sdk::ocp_receipts_stream`.
+//!
 //! In terms of UI:
 //! 1. load the page-1
 //! 2. User if scrolls-up more even after reaching end, then loads the next page & then if further
 //!    scrolls-up, then loads next page & likewise it keeps going.
 
 use colored::Colorize;
+use futures::StreamExt;
 use unifi_examples::{display_pay_receipt, init_sdk, take_input, with_spinner};
-use unifi_sdk_primitives::types::{OcPayHistory, PayHistoryFilterParams};
+use unifi_sdk_primitives::types::PayHistoryFilterParams;
 
 #[tokio::main]
 async fn main() -> eyre::Result<()> {
@@ -35,44 +38,18 @@ async fn main() -> eyre::Result<()> {
 
 	// load payment history (by latest) i.e. page-1 & then automatically Next next... unless there
 	// is no next receipts.
-	println!("========================= Page-1 =========================");
-	let OcPayHistory { receipts, .. } = with_spinner(
-		spinoff::spinners::Dots.into(),
-		"⏳ Loading Pay History...".to_string(),
-		sdk.get_ocp_receipts(user_id, true, true, None),
-		Some("✅ Pay History:".to_string()),
-		true,
-	)
-	.await
-	.unwrap_or_else(|e| panic!("{}", e.to_string().red().bold()));
-
-	let mut count = receipts.len();
-	for receipt in receipts.into_iter() {
+	let mut receipts = std::pin::pin!(sdk.ocp_receipts_stream(
+		user_id,
+		PayHistoryFilterParams { limit: Some(10), ..Default::default() },
+	));
+
+	let mut count = 0;
+	while let Some(receipt) = receipts.next().await {
+		let receipt = receipt.unwrap_or_else(|e| panic!("{}", e.to_string().red().bold()));
+		count += 1;
 		display_pay_receipt(receipt);
 	}
 
-	while let Ok(OcPayHistory { receipts, has_next, .. }) = sdk
-		.get_ocp_receipts(
-			user_id,
-			true,
-			false,
-			Some(PayHistoryFilterParams { next_or_previous: Some(true), ..Default::default() }),
-		)
-		.await
-	{
-		count += receipts.len();
-		if !receipts.is_empty() {
-			println!("========================= NEXT ... =========================");
-		}
-		for receipt in receipts.into_iter() {
-			display_pay_receipt(receipt);
-		}
-
-		if !has_next {
-			break;
-		}
-	}
-
 	println!("{}", format!("Total receipts: {}", count).bold().blue());
 
 	Ok(())
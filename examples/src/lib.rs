@@ -2,8 +2,8 @@ use colored::*;
 use spinoff::spinners::SpinnerFrames;
 use std::{io::Write, sync::LazyLock};
 use unifi_sdk_primitives::types::{
-	ChainName, OcPayReceipt, StableCoin, WalletBalancesByChain, WalletBalancesByChainCoinDetails,
-	WalletBalancesByCoin, WalletBalancesByCoinChainDetails,
+	ChainName, Contact, OcPayReceipt, StableCoin, WalletBalancesByChain,
+	WalletBalancesByChainCoinDetails, WalletBalancesByCoin, WalletBalancesByCoinChainDetails,
 };
 use unifi_sdk_rs::Sdk;
 
@@ -17,7 +17,11 @@ pub static API_KEY: LazyLock<&str> = LazyLock::new(|| {
 /// Init SDK
 pub fn init_sdk() -> Sdk {
 	dotenvy::dotenv().ok();
-	Sdk::new(*API_BASE_URL, *API_KEY)
+	Sdk::builder()
+		.api_base_url(*API_BASE_URL)
+		.api_key(*API_KEY)
+		.build()
+		.unwrap_or_else(|e| panic!("{}", e.to_string().red().bold()))
 }
 
 pub fn take_input(title: &str) -> eyre::Result<String> {
@@ -36,15 +40,15 @@ pub fn take_input(title: &str) -> eyre::Result<String> {
 	Ok(input)
 }
 
-pub async fn with_spinner<F, T>(
+pub async fn with_spinner<F, T, E>(
 	spinner_type: SpinnerFrames,
 	loading_msg: String,
 	f: F,
 	loaded_msg: Option<String>,
 	show_time: bool,
-) -> eyre::Result<T>
+) -> Result<T, E>
 where
-	F: std::future::Future<Output = eyre::Result<T>>,
+	F: std::future::Future<Output = Result<T, E>>,
 {
 	let start = std::time::Instant::now();
 	let mut spinner = spinoff::Spinner::new(spinner_type, loading_msg, spinoff::Color::Blue);
@@ -143,6 +147,24 @@ pub fn display_pay_receipt(receipt: OcPayReceipt) {
 	println!("{}", "----------------------------------------".dimmed());
 }
 
+pub fn display_contact(contact: Contact) {
+	let Contact { uid, name, address, email, note } = contact;
+	println!("{}", "----------------------------------------".dimmed());
+	println!("{} {}", "UID:".bright_blue().bold(), uid);
+	println!("{} {}", "Name:".cyan(), name);
+	println!("{} {}", "Address:".cyan(), address);
+
+	if let Some(email) = email {
+		println!("{} {}", "Email:".cyan(), email);
+	}
+
+	if let Some(note) = note {
+		println!("{} {}", "Note:".bright_yellow(), note);
+	}
+
+	println!("{}", "----------------------------------------".dimmed());
+}
+
 pub fn print_balances_by_chain(chain: ChainName, data: &WalletBalancesByChain) {
 	println!("\n{}", "================ Wallet — by Chain ================".bold().purple());
 	println!("{} {}\n", "Chain:".bold(), format!("{chain:?}").cyan());
@@ -198,6 +220,12 @@ fn print_wallet_card_chain(chain: ChainName, balance: &str, value: &str) {
 	let icon = match chain {
 		ChainName::Ethereum => "⬡",
 		ChainName::Polygon => "🟣",
+		ChainName::BnbChain => "🟡",
+		ChainName::Base => "🔵",
+		ChainName::Arbitrum => "🔷",
+		ChainName::Optimism => "🔴",
+		ChainName::PolygonZkEvm => "🟪",
+		ChainName::OpBNB => "🟨",
 		ChainName::Sepolia => "⬡",
 		ChainName::Anvil => "🛠",
 		// _ => "⚙️",
@@ -0,0 +1,98 @@
+//! Local mock of the UniFi API for offline (integration) testing of the SDK & apps.
+//!
+//! Serves every [`ApiHandler`](unifi_sdk_rs::types::ApiHandler) route with in-memory state
+//! (balances, receipts, profiles, contacts) & responds in the same [`ApiResponse`] envelope.
+//! Failures (429, 5xx, delays) can be injected per handler via [`MockState::fail`].
+//!
+//! [`ApiResponse`]: unifi_sdk_primitives::ApiResponse
+//!
+//! ## Example
+//! ```no_run
+//! use unifi_mock_server::{Failure, MockServer};
+//! use unifi_sdk_primitives::types::{ChainName, StableCoin};
+//! use unifi_sdk_rs::types::ApiHandler;
+//!
+//! # async fn run() -> std::io::Result<()> {
+//! let server = MockServer::start().await?;
+//! server.state().set_balance("alice", ChainName::Sepolia, StableCoin::USDC, "100");
+//! server.state().fail(ApiHandler::GetOcpReceipt, Failure::status(503));
+//!
+//! let sdk = server.sdk();
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{io, net::SocketAddr};
+use tokio::{
+	net::{TcpListener, ToSocketAddrs},
+	sync::oneshot,
+};
+use unifi_sdk_rs::Sdk;
+
+mod routes;
+mod state;
+
+pub use routes::router;
+pub use state::{Failure, MockState};
+
+/// Mock server running in the background until dropped.
+#[derive(Debug)]
+pub struct MockServer {
+	addr: SocketAddr,
+	state: MockState,
+	shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl MockServer {
+	/// API key accepted by default.
+	pub const DEFAULT_API_KEY: &str = "mock-api-key";
+
+	/// Start on a random local port with empty state accepting [`Self::DEFAULT_API_KEY`].
+	pub async fn start() -> io::Result<Self> {
+		Self::start_on("127.0.0.1:0", MockState::default()).await
+	}
+
+	/// Start on the `addr` with the (seeded) `state`.
+	pub async fn start_on(addr: impl ToSocketAddrs, state: MockState) -> io::Result<Self> {
+		let listener = TcpListener::bind(addr).await?;
+		let addr = listener.local_addr()?;
+		let (shutdown, shutdown_rx) = oneshot::channel();
+
+		let app = router(state.clone());
+		tokio::spawn(async move {
+			axum::serve(listener, app)
+				.with_graceful_shutdown(async {
+					shutdown_rx.await.ok();
+				})
+				.await
+		});
+
+		Ok(Self { addr, state, shutdown: Some(shutdown) })
+	}
+
+	pub fn addr(&self) -> SocketAddr {
+		self.addr
+	}
+
+	/// Base URL to be set as SDK's `api_base_url`.
+	pub fn url(&self) -> String {
+		format!("http://{}", self.addr)
+	}
+
+	pub fn state(&self) -> &MockState {
+		&self.state
+	}
+
+	/// SDK (with default settings) pointing to this server.
+	pub fn sdk(&self) -> Sdk {
+		Sdk::new(&self.url(), &self.state.api_key())
+	}
+}
+
+impl Drop for MockServer {
+	fn drop(&mut self) {
+		if let Some(shutdown) = self.shutdown.take() {
+			shutdown.send(()).ok();
+		}
+	}
+}
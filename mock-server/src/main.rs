@@ -0,0 +1,22 @@
+//! Run the mock UniFi API standalone e.g. to point an app or the examples to it.
+//!
+//! ```sh
+//! MOCK_SERVER_ADDR=127.0.0.1:8080 API_KEY=mock-api-key cargo run -p unifi-mock-server
+//! ```
+
+use tokio::net::TcpListener;
+use unifi_mock_server::{MockServer, MockState, router};
+
+const DEFAULT_ADDR: &str = "127.0.0.1:8080";
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+	let addr = std::env::var("MOCK_SERVER_ADDR").unwrap_or_else(|_| DEFAULT_ADDR.to_owned());
+	let api_key =
+		std::env::var("API_KEY").unwrap_or_else(|_| MockServer::DEFAULT_API_KEY.to_owned());
+
+	let listener = TcpListener::bind(&addr).await?;
+	println!("Mock UniFi API listening on http://{}", listener.local_addr()?);
+
+	axum::serve(listener, router(MockState::new(&api_key))).await
+}
@@ -0,0 +1,632 @@
+use crate::state::{Failure, Inner, MockState};
+use alloy_primitives::{Address, U256, keccak256};
+use axum::{
+	Json, Router,
+	body::Bytes,
+	extract::{Query, RawPathParams, State},
+	http::{HeaderMap, HeaderValue, StatusCode, Uri, header},
+	response::{IntoResponse, Response},
+	routing::{MethodFilter, on},
+};
+use serde::{Serialize, de::DeserializeOwned};
+use std::{
+	collections::HashMap,
+	fmt::{Debug, Display},
+	str::FromStr,
+	time::{SystemTime, UNIX_EPOCH},
+};
+use unifi_sdk_primitives::{
+	ApiResponse,
+	types::{
+		ChainName, ChainProtocol, Contact, ContactPayload, Mode, OcPayHistory, OcPayReceipt,
+		OcPayReceiptStatus, PayHistoryFilterParams, PayOnchainPayload, Pid, PreOcpValues,
+		StableCoin, Uid, UserProfile, WalletBalancesByChain, WalletBalancesByChainCoinDetails,
+		WalletBalancesByCoin, WalletBalancesByCoinChainDetails,
+	},
+	utils::{fmt_output, parse_human_fmt_to_u256, sanitize_address, sanitize_and_parse_amount},
+};
+use unifi_sdk_rs::{retry::IDEMPOTENCY_KEY_HEADER, types::ApiHandler};
+
+/// Amount credited per faucet request.
+const FAUCET_AMOUNT: &str = "100";
+/// Default page size of the payment history.
+const DEFAULT_RECEIPTS_LIMIT: usize = 10;
+/// Price (in USD) of every stablecoin.
+const COIN_PRICE_USD: &str = "1.00";
+
+/// Router serving every [`ApiHandler`] route.
+pub fn router(state: MockState) -> Router {
+	let mut router = Router::new();
+	for &handler in ApiHandler::all() {
+		let filter = MethodFilter::try_from(handler.method()).expect("Unsupported HTTP method");
+		router = router.route(
+			handler.path(),
+			on(filter, move |state, params, uri, headers, body| {
+				handle(state, handler, params, uri, headers, body)
+			}),
+		);
+	}
+	router.with_state(state)
+}
+
+/// Error response in the same shape as UniFi API's.
+#[derive(Debug)]
+struct MockError {
+	status: StatusCode,
+	message: String,
+}
+
+impl MockError {
+	fn new(status: StatusCode, message: impl Display) -> Self {
+		Self { status, message: message.to_string() }
+	}
+
+	fn bad_request(message: impl Display) -> Self {
+		Self::new(StatusCode::BAD_REQUEST, message)
+	}
+
+	fn not_found(message: impl Display) -> Self {
+		Self::new(StatusCode::NOT_FOUND, message)
+	}
+}
+
+impl IntoResponse for MockError {
+	fn into_response(self) -> Response {
+		let body = serde_json::json!({ "status": "error", "message": self.message });
+		(self.status, Json(body)).into_response()
+	}
+}
+
+type MockResult = Result<Response, MockError>;
+
+fn ok<T: Serialize + Debug>(data: T) -> MockResult {
+	Ok(Json(ApiResponse { status: "success".to_owned(), data }).into_response())
+}
+
+/// Path params of the matched route.
+struct Params(HashMap<String, String>);
+
+impl Params {
+	fn get(&self, name: &str) -> Result<&str, MockError> {
+		self.0
+			.get(name)
+			.map(String::as_str)
+			.ok_or_else(|| MockError::bad_request(format!("Missing param: {}", name)))
+	}
+
+	fn parse<T>(&self, name: &str) -> Result<T, MockError>
+	where
+		T: FromStr,
+		T::Err: Display,
+	{
+		self.get(name)?
+			.parse::<T>()
+			.map_err(|e| MockError::bad_request(format!("Invalid param: {}: {}", name, e)))
+	}
+}
+
+fn parse_body<T: DeserializeOwned>(body: &[u8]) -> Result<T, MockError> {
+	serde_json::from_slice(body).map_err(|e| {
+		MockError::new(StatusCode::UNPROCESSABLE_ENTITY, format!("Invalid body: {}", e))
+	})
+}
+
+async fn handle(
+	State(state): State<MockState>,
+	handler: ApiHandler,
+	params: RawPathParams,
+	uri: Uri,
+	headers: HeaderMap,
+	body: Bytes,
+) -> Response {
+	state.record_hit(handler);
+
+	let api_key = state.api_key();
+	let is_authorized = headers
+		.get(header::AUTHORIZATION)
+		.and_then(|value| value.to_str().ok())
+		.and_then(|value| value.strip_prefix("Bearer "))
+		.is_some_and(|key| key == api_key);
+	if !is_authorized {
+		return MockError::new(StatusCode::UNAUTHORIZED, "Invalid API key").into_response();
+	}
+
+	let params = Params(params.iter().map(|(k, v)| (k.to_owned(), v.to_owned())).collect());
+	let serve = || {
+		serve(&state, handler, &params, &uri, &headers, &body).unwrap_or_else(|e| e.into_response())
+	};
+
+	match state.next_failure(handler) {
+		Some(failure) if failure.after_serving => {
+			let resp = serve();
+			inject(failure).await.unwrap_or(resp)
+		},
+		Some(failure) => match inject(failure).await {
+			Some(resp) => resp,
+			None => serve(),
+		},
+		None => serve(),
+	}
+}
+
+/// Apply the failure.
+///
+/// ## Returns
+/// `None` if the request is to be served normally (after the delay).
+async fn inject(failure: Failure) -> Option<Response> {
+	let Failure { status, retry_after, delay, message, .. } = failure;
+	if let Some(delay) = delay {
+		tokio::time::sleep(delay).await;
+	}
+
+	let status = status?;
+	let message = message
+		.unwrap_or_else(|| status.canonical_reason().unwrap_or("Injected failure").to_owned());
+	let mut resp = MockError::new(status, message).into_response();
+	if let Some(retry_after) = retry_after {
+		resp.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+	}
+	Some(resp)
+}
+
+fn serve(
+	state: &MockState,
+	handler: ApiHandler,
+	params: &Params,
+	uri: &Uri,
+	headers: &HeaderMap,
+	body: &[u8],
+) -> MockResult {
+	use ApiHandler as AH;
+
+	let mut inner = state.lock();
+	match handler {
+		/* Health */
+		AH::Healthz | AH::HealthCheck => ok("OK"),
+
+		/* Profile */
+		AH::SetUserProfile => {
+			let profile = parse_body::<UserProfile>(body)?;
+			inner.profiles.insert(params.get("user_id")?.to_owned(), profile);
+			ok(())
+		},
+		AH::GetUserProfile => match inner.profiles.get(params.get("user_id")?) {
+			Some(profile) => ok(profile.clone()),
+			None => Err(MockError::not_found("User profile not found")),
+		},
+		AH::GetUserUid => {
+			let user_id = params.get("user_id")?;
+			let uid = match inner.uids.get(user_id) {
+				Some(uid) => uid.clone(),
+				None => {
+					let uid = Uid(inner.next_id("uid"));
+					inner.uids.insert(user_id.to_owned(), uid.clone());
+					uid
+				},
+			};
+			ok(uid)
+		},
+		AH::GetUserPid => {
+			let user_id = params.get("user_id")?;
+			let pid = match inner.pids.get(user_id) {
+				Some(pid) => pid.clone(),
+				None => {
+					let pid = Pid(inner.next_id("pid"));
+					inner.pids.insert(user_id.to_owned(), pid.clone());
+					pid
+				},
+			};
+			ok(pid)
+		},
+		AH::RotateUserPid => {
+			let pid = Pid(inner.next_id("pid"));
+			inner.pids.insert(params.get("user_id")?.to_owned(), pid.clone());
+			ok(pid)
+		},
+
+		/* Contacts */
+		AH::AddUserContacts => {
+			let payloads = parse_body::<Vec<ContactPayload>>(body)?;
+			validate_contacts(&payloads)?;
+
+			let added = payloads
+				.into_iter()
+				.map(|ContactPayload { name, address, email, note }| Contact {
+					uid: inner.next_id("contact"),
+					name,
+					address,
+					email,
+					note,
+				})
+				.collect::<Vec<_>>();
+			inner
+				.contacts
+				.entry(params.get("user_id")?.to_owned())
+				.or_default()
+				.extend(added.clone());
+			ok(added)
+		},
+		AH::DelUserContact => {
+			let contacts = inner.contacts.entry(params.get("user_id")?.to_owned()).or_default();
+			let uid = params.get("uid")?;
+			let len = contacts.len();
+			contacts.retain(|c| c.uid != uid);
+			if contacts.len() == len {
+				return Err(MockError::not_found("Contact not found"));
+			}
+			ok(())
+		},
+		AH::UpdateUserContact => {
+			let payload = parse_body::<ContactPayload>(body)?;
+			validate_contacts(std::slice::from_ref(&payload))?;
+
+			let contacts = inner.contacts.entry(params.get("user_id")?.to_owned()).or_default();
+			let uid = params.get("uid")?;
+			let contact = contacts
+				.iter_mut()
+				.find(|c| c.uid == uid)
+				.ok_or_else(|| MockError::not_found("Contact not found"))?;
+			let ContactPayload { name, address, email, note } = payload;
+			*contact = Contact { uid: contact.uid.clone(), name, address, email, note };
+			ok(())
+		},
+		AH::GetUserContacts =>
+			ok(inner.contacts.get(params.get("user_id")?).cloned().unwrap_or_default()),
+		AH::GetUserContactByUid => {
+			let uid = params.get("uid")?;
+			inner
+				.contacts
+				.get(params.get("user_id")?)
+				.and_then(|contacts| contacts.iter().find(|c| c.uid == uid))
+				.cloned()
+				.map_or_else(|| Err(MockError::not_found("Contact not found")), ok)
+		},
+		AH::GetUserContactsByName => {
+			let name = params.get("name")?;
+			let contacts: Vec<Contact> = inner
+				.contacts
+				.get(params.get("user_id")?)
+				.map(|contacts| {
+					contacts.iter().filter(|c| c.name.eq_ignore_ascii_case(name)).cloned().collect()
+				})
+				.unwrap_or_default();
+			ok(contacts)
+		},
+
+		/* Wallet */
+		AH::GetUserWalletAddress => {
+			params.parse::<ChainName>("chain")?;
+			ok(wallet_address(params.get("user_id")?))
+		},
+		AH::GetUserWalletAddresses => {
+			let address = wallet_address(params.get("user_id")?);
+			ok(HashMap::from([(ChainProtocol::Evm, address)]))
+		},
+		AH::GetOcChainCoinBalance => {
+			let (chain, coin) = (params.parse("chain")?, params.parse::<StableCoin>("coin")?);
+			let decimals = decimals(coin, chain)?;
+			let balance = inner.balance(params.get("user_id")?, chain, coin);
+			ok(fmt(balance, decimals))
+		},
+		AH::GetOcChainAllCoinsBalances => {
+			let (user_id, chain) = (params.get("user_id")?, params.parse("chain")?);
+			// only the coins on the chain
+			let balances = StableCoin::all()
+				.iter()
+				.filter_map(|&coin| {
+					let decimals = coin.decimals_on(chain).ok()?;
+					Some((coin, fmt(inner.balance(user_id, chain, coin), decimals)))
+				})
+				.collect::<HashMap<_, _>>();
+			ok(balances)
+		},
+		AH::GetWalletBalancesByChain => {
+			let (user_id, chain) = (params.get("user_id")?, params.parse("chain")?);
+			// (coin, decimals, balance) of the coins on the chain
+			let balances = StableCoin::all()
+				.iter()
+				.filter_map(|&coin| {
+					let decimals = coin.decimals_on(chain).ok()?;
+					Some((coin, decimals, inner.balance(user_id, chain, coin)))
+				})
+				.collect::<Vec<_>>();
+			let coin_details = balances
+				.iter()
+				.map(|&(coin, decimals, balance)| {
+					let balance = fmt_2dp(balance, decimals);
+					let details = WalletBalancesByChainCoinDetails {
+						price_usd: COIN_PRICE_USD.to_owned(),
+						balance: balance.clone(),
+						value_usd: balance,
+					};
+					(coin, details)
+				})
+				.collect();
+			let balances = balances
+				.iter()
+				.map(|&(_, decimals, balance)| (decimals, balance))
+				.collect::<Vec<_>>();
+			ok(WalletBalancesByChain { total_usd: total_usd(&balances), coin_details })
+		},
+		AH::GetWalletBalancesByCoin => {
+			let (user_id, coin) = (params.get("user_id")?, params.parse::<StableCoin>("coin")?);
+			// (chain, decimals, balance) of the chains with the coin
+			let balances = ChainName::all(Mode::Test)
+				.iter()
+				.filter_map(|&chain| {
+					let decimals = coin.decimals_on(chain).ok()?;
+					Some((chain, decimals, inner.balance(user_id, chain, coin)))
+				})
+				.collect::<Vec<_>>();
+			let chain_details = balances
+				.iter()
+				.map(|&(chain, decimals, balance)| {
+					let balance = fmt_2dp(balance, decimals);
+					let details = WalletBalancesByCoinChainDetails {
+						balance: balance.clone(),
+						value_usd: balance,
+					};
+					(chain, details)
+				})
+				.collect();
+			let balances = balances
+				.iter()
+				.map(|&(_, decimals, balance)| (decimals, balance))
+				.collect::<Vec<_>>();
+			ok(WalletBalancesByCoin {
+				total_usd: total_usd(&balances),
+				price_usd: COIN_PRICE_USD.to_owned(),
+				chain_details,
+			})
+		},
+
+		/* Payment Onchain */
+		AH::FetchPreOcpNetOnchainBalance => {
+			let (chain, coin) = (params.parse("chain")?, params.parse::<StableCoin>("coin")?);
+			let decimals = decimals(coin, chain)?;
+			let balance = inner.balance(params.get("user_id")?, chain, coin);
+			ok(fmt(balance.saturating_sub(inner.est_fee(decimals)), decimals))
+		},
+		AH::FetchPreOcpTotalEstFees => {
+			let (chain, coin) = (params.parse("chain")?, params.parse::<StableCoin>("coin")?);
+			let decimals = decimals(coin, chain)?;
+			ok(fmt(inner.est_fee(decimals), decimals))
+		},
+		AH::FetchPreOcpBalanceAndEstFees => {
+			let (chain, coin) = (params.parse("chain")?, params.parse::<StableCoin>("coin")?);
+			let decimals = decimals(coin, chain)?;
+			let balance = inner.balance(params.get("user_id")?, chain, coin);
+			ok(PreOcpValues {
+				is_coin_allowance_zero: false,
+				balance: fmt(balance, decimals),
+				est_fee: fmt(inner.est_fee(decimals), decimals),
+			})
+		},
+		AH::RequestFaucet => {
+			let (chain, coin) =
+				(params.parse::<ChainName>("chain")?, params.parse::<StableCoin>("coin")?);
+			if !matches!(chain, ChainName::Sepolia | ChainName::Anvil) {
+				return Err(MockError::bad_request(format!("Faucet not available on {}", chain)));
+			}
+
+			let amount = parse_human_fmt_to_u256(FAUCET_AMOUNT, decimals(coin, chain)?, false)
+				.map_err(MockError::bad_request)?;
+			let key = (params.get("user_id")?.to_owned(), chain, coin);
+			*inner.balances.entry(key).or_default() += amount;
+			ok(())
+		},
+		AH::PayOnchain => pay_onchain(&mut inner, params, headers, body),
+		AH::FliqNotifyPayer => {
+			let pid = params.get("pid")?;
+			if !inner.pids.values().any(|p| p.0 == pid) {
+				return Err(MockError::not_found("Payer not found"));
+			}
+			params.parse::<ChainName>("chain")?;
+			params.parse::<StableCoin>("coin")?;
+			if !sanitize_address(params.get("to_address")?) {
+				return Err(MockError::bad_request("Invalid address"));
+			}
+			ok(())
+		},
+		AH::GetOcpReceipt => {
+			let auto_advance = inner.auto_advance_receipts;
+			let receipt_id = params.get("receipt_id")?;
+			let receipt = inner
+				.receipts
+				.iter_mut()
+				.find(|r| r.id == receipt_id)
+				.ok_or_else(|| MockError::not_found("Receipt not found"))?;
+			let fetched = receipt.clone();
+			if auto_advance {
+				advance(receipt);
+			}
+			ok(fetched)
+		},
+		AH::GetOcpReceipts => {
+			let filter = Query::<PayHistoryFilterParams>::try_from_uri(uri)
+				.map_err(MockError::bad_request)?
+				.0;
+			let sort_by_latest = params.parse::<bool>("sort_by_latest")?;
+			let from_start = params.parse::<bool>("from_start")?;
+			ok(receipts_page(
+				&mut inner,
+				params.get("user_id")?,
+				sort_by_latest,
+				from_start,
+				filter,
+			))
+		},
+	}
+}
+
+fn validate_contacts(contacts: &[ContactPayload]) -> Result<(), MockError> {
+	match contacts.iter().find(|c| !sanitize_address(&c.address)) {
+		Some(contact) =>
+			Err(MockError::bad_request(format!("Invalid address: {}", contact.address))),
+		None => Ok(()),
+	}
+}
+
+fn pay_onchain(inner: &mut Inner, params: &Params, headers: &HeaderMap, body: &[u8]) -> MockResult {
+	let user_id = params.get("user_id")?;
+	let is_fee_incl = params.parse::<bool>("is_fee_incl")?;
+	let payload = parse_body::<PayOnchainPayload>(body)?;
+
+	let idempotency_key = headers
+		.get(IDEMPOTENCY_KEY_HEADER)
+		.map(|key| key.to_str().map(str::to_owned))
+		.transpose()
+		.map_err(|_| MockError::bad_request("Invalid idempotency key"))?;
+	if let Some(key) = &idempotency_key &&
+		let Some(receipt_id) = inner.idempotency_keys.get(key)
+	{
+		return Err(MockError::new(
+			StatusCode::CONFLICT,
+			format!("Duplicate payment. Receipt ID: {}", receipt_id),
+		));
+	}
+
+	let PayOnchainPayload { chain, coin, to_address, amount, memo } = payload;
+	if !sanitize_address(&to_address) {
+		return Err(MockError::bad_request(format!("Invalid address: {}", to_address)));
+	}
+	let amount_u256 =
+		sanitize_and_parse_amount(&amount, coin, chain).map_err(MockError::bad_request)?;
+	let decimals = decimals(coin, chain)?;
+	let est_fee = inner.est_fee(decimals);
+	let total = if is_fee_incl { amount_u256 } else { amount_u256 + est_fee };
+	if est_fee >= total {
+		return Err(MockError::bad_request("Amount doesn't cover the fee"));
+	}
+
+	let key = (user_id.to_owned(), chain, coin);
+	let balance = inner.balances.get(&key).copied().unwrap_or_default();
+	if total > balance {
+		return Err(MockError::bad_request("Insufficient balance"));
+	}
+	inner.balances.insert(key, balance - total);
+
+	let id = inner.next_id("receipt");
+	if let Some(key) = idempotency_key {
+		inner.idempotency_keys.insert(key, id.clone());
+	}
+	let est_fee = fmt(est_fee, decimals);
+	inner.receipts.push(OcPayReceipt {
+		id: id.clone(),
+		entity: "mock".to_owned(),
+		user_id: user_id.to_owned(),
+		is_fee_incl,
+		chain,
+		coin,
+		to_address,
+		amount,
+		memo,
+		act_fee: est_fee.clone(),
+		est_fee,
+		status: OcPayReceiptStatus::Processing,
+		start_ts_us: now_us(),
+		..Default::default()
+	});
+
+	ok(id)
+}
+
+/// Move the receipt one status ahead in its happy path.
+fn advance(receipt: &mut OcPayReceipt) {
+	use OcPayReceiptStatus as S;
+	match receipt.status {
+		S::Processing => {
+			receipt.status = S::Confirmed;
+			receipt.tx_hash = keccak256(receipt.id.as_bytes()).to_string();
+			receipt.block_num = 1;
+		},
+		S::Confirmed => {
+			receipt.status = S::Finalized;
+			receipt.end_ts_us = now_us();
+		},
+		S::Finalized | S::Failed | S::Reorged => {},
+	}
+}
+
+/// Serve the next/previous page of the user's receipts, keeping a cursor per user like the API.
+fn receipts_page(
+	inner: &mut Inner,
+	user_id: &str,
+	sort_by_latest: bool,
+	from_start: bool,
+	filter: PayHistoryFilterParams,
+) -> OcPayHistory {
+	let PayHistoryFilterParams { chain, status, limit, next_or_previous } = filter;
+	let limit = limit
+		.and_then(|limit| usize::try_from(limit).ok())
+		.filter(|&limit| limit > 0)
+		.unwrap_or(DEFAULT_RECEIPTS_LIMIT);
+
+	let mut receipts = inner
+		.receipts
+		.iter()
+		.filter(|r| r.user_id == user_id)
+		.filter(|r| chain.is_none_or(|chain| r.chain == chain))
+		.filter(|r| status.is_none_or(|status| r.status == status))
+		.cloned()
+		.collect::<Vec<_>>();
+	if sort_by_latest {
+		receipts.reverse();
+	}
+
+	let last_page = receipts.len().saturating_sub(1) / limit;
+	let page = inner.receipt_pages.entry(user_id.to_owned()).or_default();
+	*page = match (from_start, next_or_previous) {
+		(true, _) => 0,
+		(false, Some(true)) => (*page + 1).min(last_page),
+		(false, Some(false)) => page.saturating_sub(1),
+		(false, None) => (*page).min(last_page),
+	};
+
+	let page = *page;
+	OcPayHistory {
+		has_prev: page > 0,
+		receipts: receipts.into_iter().skip(page * limit).take(limit).collect(),
+		has_next: page < last_page,
+	}
+}
+
+/// Deterministic (fake) EVM address of the user.
+fn wallet_address(user_id: &str) -> String {
+	Address::from_slice(&keccak256(user_id.as_bytes())[12..]).to_checksum(None)
+}
+
+/// Decimals of the coin on the chain. Errs (as `400`) if the coin isn't on the chain.
+fn decimals(coin: StableCoin, chain: ChainName) -> Result<u8, MockError> {
+	coin.decimals_on(chain).map_err(MockError::bad_request)
+}
+
+fn fmt(value: U256, decimals: u8) -> String {
+	fmt_output(value, decimals).unwrap_or_default()
+}
+
+/// Format in 2 decimals (truncated) as shown in the wallet page.
+fn fmt_2dp(value: U256, decimals: u8) -> String {
+	let value = fmt_output(value, decimals).unwrap_or_default();
+	let (whole, frac) = value.split_once('.').unwrap_or((&value, ""));
+	format!("{}.{:0<2}", whole, &frac[..frac.len().min(2)])
+}
+
+/// Total value (in USD) of the balances, given every coin is priced at [`COIN_PRICE_USD`].
+///
+/// NOTE: `balances` as (decimals, balance).
+fn total_usd(balances: &[(u8, U256)]) -> String {
+	const DECIMALS: u8 = 18;
+	let total = balances.iter().fold(U256::ZERO, |total, &(decimals, balance)| {
+		let scale = U256::from(10).pow(U256::from(DECIMALS.abs_diff(decimals)));
+		total + if decimals <= DECIMALS { balance * scale } else { balance / scale }
+	});
+	fmt_2dp(total, DECIMALS)
+}
+
+fn now_us() -> i64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_micros() as i64)
+		.unwrap_or_default()
+}
@@ -0,0 +1,279 @@
+use alloy_primitives::U256;
+use axum::http::StatusCode;
+use std::{
+	collections::{HashMap, VecDeque},
+	sync::{Arc, Mutex, MutexGuard},
+	time::Duration,
+};
+use unifi_sdk_primitives::{
+	types::{
+		ChainName, Contact, OcPayReceipt, OcPayReceiptStatus, Pid, StableCoin, Uid, UserProfile,
+	},
+	utils::{fmt_output, parse_human_fmt_to_u256},
+};
+use unifi_sdk_rs::types::ApiHandler;
+
+/// Failure injected into the response of a handler.
+///
+/// ## Example
+/// ```
+/// use std::time::Duration;
+/// use unifi_mock_server::Failure;
+///
+/// // 429 with `Retry-After: 1`
+/// let rate_limited = Failure::rate_limited(Some(1));
+/// // 503 after 2s
+/// let unavailable = Failure::status(503).with_delay(Duration::from_secs(2));
+/// // served normally, but after 2s
+/// let slow = Failure::delay(Duration::from_secs(2));
+/// // served (i.e. state updated), but the response is lost as 502
+/// let lost = Failure::status(502).after_serving();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Failure {
+	pub(crate) status: Option<StatusCode>,
+	pub(crate) retry_after: Option<u64>,
+	pub(crate) delay: Option<Duration>,
+	pub(crate) message: Option<String>,
+	pub(crate) after_serving: bool,
+}
+
+impl Failure {
+	/// Respond with the `status` code.
+	///
+	/// NOTE: Panics if `status` isn't a valid HTTP status code.
+	pub fn status(status: u16) -> Self {
+		let status = StatusCode::from_u16(status).expect("Invalid HTTP status code");
+		Self { status: Some(status), ..Default::default() }
+	}
+
+	/// Respond with `429`, with `Retry-After` (in secs) if set.
+	pub fn rate_limited(retry_after: Option<u64>) -> Self {
+		Self { status: Some(StatusCode::TOO_MANY_REQUESTS), retry_after, ..Default::default() }
+	}
+
+	/// Serve the request normally, but after the `delay`.
+	pub fn delay(delay: Duration) -> Self {
+		Self { delay: Some(delay), ..Default::default() }
+	}
+
+	/// Wait for the `delay` before responding.
+	pub fn with_delay(mut self, delay: Duration) -> Self {
+		self.delay = Some(delay);
+		self
+	}
+
+	/// Error message in the response body.
+	pub fn with_message(mut self, message: &str) -> Self {
+		self.message = Some(message.to_owned());
+		self
+	}
+
+	/// Serve the request (applying its side effects) before failing, like a response lost on the
+	/// way back.
+	pub fn after_serving(mut self) -> Self {
+		self.after_serving = true;
+		self
+	}
+}
+
+/// In-memory state of the mock server, shared between the server & the test.
+///
+/// Cloning is cheap as the state is behind an `Arc`.
+#[derive(Debug, Clone)]
+pub struct MockState(Arc<Mutex<Inner>>);
+
+#[derive(Debug)]
+pub(crate) struct Inner {
+	pub(crate) api_key: String,
+	/// Formatted est. fee charged on every payment irrespective of coin & chain.
+	pub(crate) est_fee: String,
+	/// Advance a receipt one status (`Processing` -> `Confirmed` -> `Finalized`) on every fetch.
+	pub(crate) auto_advance_receipts: bool,
+	/// (user_id, chain, coin) -> balance
+	pub(crate) balances: HashMap<(String, ChainName, StableCoin), U256>,
+	pub(crate) profiles: HashMap<String, UserProfile>,
+	/// Issued on first fetch.
+	pub(crate) uids: HashMap<String, Uid>,
+	/// Issued on first fetch & replaced on rotation.
+	pub(crate) pids: HashMap<String, Pid>,
+	pub(crate) contacts: HashMap<String, Vec<Contact>>,
+	/// In the order of creation.
+	pub(crate) receipts: Vec<OcPayReceipt>,
+	/// user_id -> page (of receipts) last served
+	pub(crate) receipt_pages: HashMap<String, usize>,
+	/// idempotency key -> receipt id
+	pub(crate) idempotency_keys: HashMap<String, String>,
+	pub(crate) failures: HashMap<ApiHandler, VecDeque<Failure>>,
+	pub(crate) hits: HashMap<ApiHandler, usize>,
+	pub(crate) next_id: u64,
+}
+
+impl Inner {
+	pub(crate) fn next_id(&mut self, prefix: &str) -> String {
+		self.next_id += 1;
+		format!("{}-{}", prefix, self.next_id)
+	}
+
+	pub(crate) fn balance(&self, user_id: &str, chain: ChainName, coin: StableCoin) -> U256 {
+		self.balances
+			.get(&(user_id.to_owned(), chain, coin))
+			.copied()
+			.unwrap_or_default()
+	}
+
+	/// Est. fee in the coin's smallest unit, given its `decimals` on the chain.
+	pub(crate) fn est_fee(&self, decimals: u8) -> U256 {
+		parse_human_fmt_to_u256(&self.est_fee, decimals, false).unwrap_or_default()
+	}
+}
+
+impl Default for MockState {
+	fn default() -> Self {
+		Self::new(crate::MockServer::DEFAULT_API_KEY)
+	}
+}
+
+impl MockState {
+	/// Default est. fee charged on every payment.
+	pub const DEFAULT_EST_FEE: &str = "0.1";
+
+	/// Empty state accepting only the `api_key` (as `Bearer` token).
+	pub fn new(api_key: &str) -> Self {
+		Self(Arc::new(Mutex::new(Inner {
+			api_key: api_key.to_owned(),
+			est_fee: Self::DEFAULT_EST_FEE.to_owned(),
+			auto_advance_receipts: true,
+			balances: HashMap::new(),
+			profiles: HashMap::new(),
+			uids: HashMap::new(),
+			pids: HashMap::new(),
+			contacts: HashMap::new(),
+			receipts: Vec::new(),
+			receipt_pages: HashMap::new(),
+			idempotency_keys: HashMap::new(),
+			failures: HashMap::new(),
+			hits: HashMap::new(),
+			next_id: 0,
+		})))
+	}
+
+	pub(crate) fn lock(&self) -> MutexGuard<'_, Inner> {
+		// A panicking test mustn't poison the state for the others.
+		self.0.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	pub fn api_key(&self) -> String {
+		self.lock().api_key.clone()
+	}
+
+	/// Set the user's balance (formatted e.g. "100.5") of a coin on a chain.
+	///
+	/// NOTE: Panics if `balance` isn't a valid amount or the coin isn't on the chain.
+	pub fn set_balance(&self, user_id: &str, chain: ChainName, coin: StableCoin, balance: &str) {
+		let decimals = coin.decimals_on(chain).unwrap_or_else(|e| panic!("{}", e));
+		let balance = parse_human_fmt_to_u256(balance, decimals, false)
+			.unwrap_or_else(|e| panic!("Invalid balance: {}: {}", balance, e));
+		self.lock().balances.insert((user_id.to_owned(), chain, coin), balance);
+	}
+
+	/// User's (formatted) balance of a coin on a chain.
+	///
+	/// NOTE: Panics if the coin isn't on the chain.
+	pub fn balance(&self, user_id: &str, chain: ChainName, coin: StableCoin) -> String {
+		let decimals = coin.decimals_on(chain).unwrap_or_else(|e| panic!("{}", e));
+		let balance = self.lock().balance(user_id, chain, coin);
+		fmt_output(balance, decimals).unwrap_or_default()
+	}
+
+	/// Set the (formatted) est. fee charged on every payment. Default: [`Self::DEFAULT_EST_FEE`].
+	pub fn set_est_fee(&self, est_fee: &str) {
+		self.lock().est_fee = est_fee.to_owned();
+	}
+
+	/// Advance a `Processing` receipt one status on every fetch, until `Finalized`. Default:
+	/// `true`.
+	///
+	/// Set `false` to control the status via [`Self::set_receipt_status`] instead.
+	pub fn set_auto_advance_receipts(&self, auto_advance: bool) {
+		self.lock().auto_advance_receipts = auto_advance;
+	}
+
+	pub fn set_profile(&self, user_id: &str, profile: UserProfile) {
+		self.lock().profiles.insert(user_id.to_owned(), profile);
+	}
+
+	pub fn profile(&self, user_id: &str) -> Option<UserProfile> {
+		self.lock().profiles.get(user_id).cloned()
+	}
+
+	/// User's current PID, if issued.
+	pub fn pid(&self, user_id: &str) -> Option<Pid> {
+		self.lock().pids.get(user_id).cloned()
+	}
+
+	pub fn contacts(&self, user_id: &str) -> Vec<Contact> {
+		self.lock().contacts.get(user_id).cloned().unwrap_or_default()
+	}
+
+	/// Add a receipt as is e.g. to seed the payment history.
+	pub fn insert_receipt(&self, receipt: OcPayReceipt) {
+		self.lock().receipts.push(receipt);
+	}
+
+	pub fn receipt(&self, receipt_id: &str) -> Option<OcPayReceipt> {
+		self.lock().receipts.iter().find(|r| r.id == receipt_id).cloned()
+	}
+
+	/// All the receipts of a user in the order of creation.
+	pub fn receipts(&self, user_id: &str) -> Vec<OcPayReceipt> {
+		self.lock().receipts.iter().filter(|r| r.user_id == user_id).cloned().collect()
+	}
+
+	/// Set the status of a receipt.
+	///
+	/// ## Returns
+	/// `false` if the receipt isn't found.
+	pub fn set_receipt_status(&self, receipt_id: &str, status: OcPayReceiptStatus) -> bool {
+		let mut inner = self.lock();
+		match inner.receipts.iter_mut().find(|r| r.id == receipt_id) {
+			Some(receipt) => {
+				receipt.status = status;
+				true
+			},
+			None => false,
+		}
+	}
+
+	/// Inject a failure into the next request of the handler.
+	///
+	/// Failures are queued i.e. call it `n` times to fail the next `n` requests.
+	pub fn fail(&self, handler: ApiHandler, failure: Failure) {
+		self.lock().failures.entry(handler).or_default().push_back(failure);
+	}
+
+	/// Inject the same failure into the next `times` requests of the handler.
+	pub fn fail_times(&self, handler: ApiHandler, failure: Failure, times: usize) {
+		let mut inner = self.lock();
+		let failures = inner.failures.entry(handler).or_default();
+		failures.extend(std::iter::repeat_n(failure, times));
+	}
+
+	/// Remove all the pending failures.
+	pub fn clear_failures(&self) {
+		self.lock().failures.clear();
+	}
+
+	pub(crate) fn next_failure(&self, handler: ApiHandler) -> Option<Failure> {
+		self.lock().failures.get_mut(&handler)?.pop_front()
+	}
+
+	/// No. of requests received for the handler (incl. the failed ones).
+	pub fn hits(&self, handler: ApiHandler) -> usize {
+		self.lock().hits.get(&handler).copied().unwrap_or_default()
+	}
+
+	pub(crate) fn record_hit(&self, handler: ApiHandler) {
+		*self.lock().hits.entry(handler).or_default() += 1;
+	}
+}
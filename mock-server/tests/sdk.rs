@@ -0,0 +1,367 @@
+//! End-to-end tests of the SDK against the mock server.
+//!
+//! ```sh
+//! RUSTFLAGS="-Awarnings" cargo t -p unifi-mock-server --test sdk -- --show-output
+//! ```
+
+use futures::{StreamExt, TryStreamExt};
+use std::time::Duration;
+use unifi_mock_server::{Failure, MockServer};
+use unifi_sdk_primitives::types::{
+	ChainName, ContactPayload, Memo, OcPayReceipt, OcPayReceiptStatus, PayHistoryFilterParams,
+	PayOnchainPayload, PreOcpPayload, StableCoin,
+};
+use unifi_sdk_rs::{
+	Sdk,
+	errors::SdkError,
+	retry::RetryPolicy,
+	types::{ApiHandler, WaitForReceiptOpts},
+};
+
+const USER: &str = "alice";
+const PAYEE: &str = "0xDA741C58b3e299A8c51Aa80DF70AB2881d17499c";
+
+/// SDK retrying without (long) delays.
+fn fast_sdk(server: &MockServer) -> Sdk {
+	Sdk {
+		retry_policy: RetryPolicy {
+			base_delay: Duration::from_millis(10),
+			jitter: false,
+			..Default::default()
+		},
+		..server.sdk()
+	}
+}
+
+fn payment(amount: &str) -> PayOnchainPayload {
+	PayOnchainPayload {
+		chain: ChainName::Sepolia,
+		coin: StableCoin::USDC,
+		to_address: PAYEE.to_owned(),
+		amount: amount.to_owned(),
+		memo: Memo::General,
+	}
+}
+
+#[tokio::test]
+async fn checks_api_key() {
+	let server = MockServer::start().await.unwrap();
+
+	assert_eq!(server.sdk().healthz().await.unwrap(), "OK");
+	assert!(matches!(
+		Sdk::new(&server.url(), "wrong-key").health_check().await,
+		Err(SdkError::Unauthorized { .. })
+	));
+}
+
+#[tokio::test]
+async fn retries_transient_failures() {
+	let server = MockServer::start().await.unwrap();
+	let sdk = fast_sdk(&server);
+	let handler = ApiHandler::GetUserContacts;
+
+	server.state().fail_times(handler, Failure::status(503), 2);
+	assert!(sdk.get_user_contacts(USER).await.unwrap().is_empty());
+	assert_eq!(server.state().hits(handler), 3);
+
+	// attempts exhausted
+	server.state().fail_times(handler, Failure::status(502).with_message("down"), 3);
+	let err = sdk.get_user_contacts(USER).await.unwrap_err();
+	assert!(matches!(&err, SdkError::Server { status: 502, body } if body.message == "down"));
+	assert_eq!(server.state().hits(handler), 6);
+}
+
+#[tokio::test]
+async fn honors_retry_after() {
+	let server = MockServer::start().await.unwrap();
+	let handler = ApiHandler::GetUserProfile;
+
+	server.state().fail(handler, Failure::rate_limited(Some(7)));
+	let err = Sdk { retry_policy: RetryPolicy::none(), ..server.sdk() }
+		.get_user_profile(USER)
+		.await
+		.unwrap_err();
+	assert!(matches!(
+		err,
+		SdkError::RateLimited { retry_after: Some(d), .. } if d == Duration::from_secs(7)
+	));
+
+	server.state().fail(handler, Failure::rate_limited(Some(0)));
+	assert!(matches!(
+		fast_sdk(&server).get_user_profile(USER).await,
+		Err(SdkError::NotFound { .. })
+	));
+	assert_eq!(server.state().hits(handler), 3);
+}
+
+#[tokio::test]
+async fn times_out_on_delay() {
+	let server = MockServer::start().await.unwrap();
+	let sdk = Sdk::builder()
+		.api_base_url(&server.url())
+		.api_key(MockServer::DEFAULT_API_KEY)
+		.retry_policy(RetryPolicy::none())
+		.timeout(Duration::from_millis(100))
+		.build()
+		.unwrap();
+
+	server
+		.state()
+		.fail(ApiHandler::Healthz, Failure::delay(Duration::from_millis(500)));
+	assert!(matches!(sdk.healthz().await, Err(SdkError::Transport(e)) if e.is_timeout()));
+	assert_eq!(sdk.healthz().await.unwrap(), "OK");
+}
+
+#[tokio::test]
+async fn pays_onchain() {
+	let server = MockServer::start().await.unwrap();
+	let sdk = fast_sdk(&server);
+	let state = server.state();
+	state.set_balance(USER, ChainName::Sepolia, StableCoin::USDC, "10");
+
+	let values = sdk
+		.fetch_pre_ocp_balance_and_est_fee(
+			USER,
+			PreOcpPayload { coin: StableCoin::USDC, chain: ChainName::Sepolia },
+		)
+		.await
+		.unwrap();
+	assert_eq!((values.balance.as_str(), values.est_fee.as_str()), ("10.000000", "0.100000"));
+
+	// not retried without idempotency key
+	state.fail(ApiHandler::PayOnchain, Failure::status(503));
+	assert!(matches!(
+		sdk.pay_onchain(USER, false, payment("2")).await,
+		Err(SdkError::Server { status: 503, .. })
+	));
+	assert_eq!(state.hits(ApiHandler::PayOnchain), 1);
+
+	// retried with idempotency key, but processed once
+	let key = payment("2").idempotency_key(USER);
+	state.fail(ApiHandler::PayOnchain, Failure::status(503));
+	let receipt_id = sdk
+		.pay_onchain_with_idempotency_key(USER, false, payment("2"), &key)
+		.await
+		.unwrap();
+	assert_eq!(state.balance(USER, ChainName::Sepolia, StableCoin::USDC), "7.900000");
+	assert!(matches!(
+		sdk.pay_onchain_with_idempotency_key(USER, false, payment("2"), &key).await,
+		Err(SdkError::DuplicatePayment { receipt_id: Some(id), .. }) if id == receipt_id
+	));
+
+	assert!(matches!(
+		sdk.pay_onchain(USER, true, payment("8")).await,
+		Err(SdkError::Client { status: 400, body }) if body.message == "Insufficient balance"
+	));
+
+	let opts =
+		WaitForReceiptOpts { poll_interval: Duration::from_millis(10), ..Default::default() };
+	let receipt = sdk
+		.wait_for_receipt(&receipt_id, OcPayReceiptStatus::Finalized, opts.clone())
+		.await
+		.unwrap();
+	assert_eq!(receipt.status, OcPayReceiptStatus::Finalized);
+	assert_eq!(receipt.amount, "2");
+
+	let receipt_id = sdk.pay_onchain(USER, true, payment("1")).await.unwrap();
+	state.set_auto_advance_receipts(false);
+	state.set_receipt_status(&receipt_id, OcPayReceiptStatus::Failed);
+	assert!(matches!(
+		sdk.wait_for_receipt(&receipt_id, OcPayReceiptStatus::Confirmed, opts).await,
+		Err(SdkError::PaymentFailed(_))
+	));
+}
+
+#[tokio::test]
+async fn recovers_lost_payment_response() {
+	let server = MockServer::start().await.unwrap();
+	let sdk = fast_sdk(&server);
+	let state = server.state();
+	state.set_balance(USER, ChainName::Sepolia, StableCoin::USDC, "10");
+
+	// 1st attempt is paid, but its response is lost. So, the retry is a duplicate.
+	let key = payment("2").idempotency_key(USER);
+	state.fail(ApiHandler::PayOnchain, Failure::status(503).after_serving());
+	let receipt_id =
+		match sdk.pay_onchain_with_idempotency_key(USER, false, payment("2"), &key).await {
+			Err(SdkError::DuplicatePayment { idempotency_key, receipt_id: Some(id), .. }) => {
+				assert_eq!(idempotency_key, key);
+				id
+			},
+			res => panic!("Expected duplicate payment with receipt ID, got: {:?}", res),
+		};
+	assert_eq!(state.hits(ApiHandler::PayOnchain), 2);
+
+	// paid once & the receipt is of that payment
+	assert_eq!(state.balance(USER, ChainName::Sepolia, StableCoin::USDC), "7.900000");
+	let receipt = sdk.get_ocp_receipt(&receipt_id).await.unwrap();
+	assert_eq!(receipt.amount, "2");
+}
+
+#[tokio::test]
+async fn bounds_wait_for_receipt() {
+	let server = MockServer::start().await.unwrap();
+	let sdk = server.sdk();
+	server.state().set_balance(USER, ChainName::Sepolia, StableCoin::USDC, "10");
+	let receipt_id = sdk.pay_onchain(USER, true, payment("1")).await.unwrap();
+
+	// stalled server
+	server
+		.state()
+		.fail(ApiHandler::GetOcpReceipt, Failure::delay(Duration::from_secs(5)));
+	let opts = WaitForReceiptOpts { timeout: Duration::from_millis(200), ..Default::default() };
+	let start = std::time::Instant::now();
+	assert!(matches!(
+		sdk.wait_for_receipt(&receipt_id, OcPayReceiptStatus::Finalized, opts).await,
+		Err(SdkError::WaitTimeout { last_status: OcPayReceiptStatus::Processing, .. })
+	));
+	assert!(start.elapsed() < Duration::from_secs(2));
+}
+
+#[tokio::test]
+async fn streams_receipts() {
+	let server = MockServer::start().await.unwrap();
+	let sdk = server.sdk();
+	for i in 0..25 {
+		server.state().insert_receipt(OcPayReceipt {
+			id: format!("receipt-{}", i),
+			user_id: USER.to_owned(),
+			chain: if i % 5 == 0 { ChainName::Polygon } else { ChainName::Sepolia },
+			..Default::default()
+		});
+	}
+
+	let filter = PayHistoryFilterParams { limit: Some(10), ..Default::default() };
+	let receipts = sdk.ocp_receipts_stream(USER, filter).try_collect::<Vec<_>>().await.unwrap();
+	let ids = receipts.iter().map(|r| r.id.as_str()).collect::<Vec<_>>();
+	assert_eq!(ids.len(), 25);
+	assert_eq!((ids[0], ids[24]), ("receipt-24", "receipt-0"));
+
+	let filter = PayHistoryFilterParams {
+		chain: Some(ChainName::Polygon),
+		limit: Some(2),
+		..Default::default()
+	};
+	let (receipts, is_truncated) = sdk.collect_all_ocp_receipts(USER, filter, 4).await.unwrap();
+	let ids = receipts.iter().map(|r| r.id.as_str()).collect::<Vec<_>>();
+	assert_eq!(ids, ["receipt-20", "receipt-15", "receipt-10", "receipt-5"]);
+	assert!(is_truncated);
+
+	let (receipts, is_truncated) = sdk.collect_all_ocp_receipts(USER, filter, 5).await.unwrap();
+	assert_eq!((receipts.len(), is_truncated), (5, false));
+}
+
+/// The server moves the page cursor, but the response is lost. Retrying would serve the page
+/// after the lost one, so the stream must fail instead of skipping it.
+#[tokio::test]
+async fn never_skips_receipts() {
+	let server = MockServer::start().await.unwrap();
+	let sdk = fast_sdk(&server);
+	for i in 0..6 {
+		server.state().insert_receipt(OcPayReceipt {
+			id: format!("receipt-{}", i),
+			user_id: USER.to_owned(),
+			..Default::default()
+		});
+	}
+	let handler = ApiHandler::GetOcpReceipts;
+	let filter = PayHistoryFilterParams { limit: Some(2), ..Default::default() };
+
+	// page-1 is served, page-2 is lost
+	server.state().fail(handler, Failure::delay(Duration::ZERO));
+	server.state().fail(handler, Failure::status(503).after_serving());
+	let results = sdk.ocp_receipts_stream(USER, filter).collect::<Vec<_>>().await;
+	assert_eq!(server.state().hits(handler), 2);
+	assert_eq!(results.len(), 3);
+	let ids = results[..2].iter().map(|r| r.as_ref().unwrap().id.as_str()).collect::<Vec<_>>();
+	assert_eq!(ids, ["receipt-5", "receipt-4"]);
+	assert!(matches!(results[2], Err(SdkError::Server { status: 503, .. })));
+
+	server.state().fail(handler, Failure::delay(Duration::ZERO));
+	server.state().fail(handler, Failure::status(503).after_serving());
+	assert!(sdk.collect_all_ocp_receipts(USER, filter, 10).await.is_err());
+
+	// restarting loads all of them
+	let (receipts, is_truncated) = sdk.collect_all_ocp_receipts(USER, filter, 10).await.unwrap();
+	let ids = receipts.iter().map(|r| r.id.as_str()).collect::<Vec<_>>();
+	assert_eq!(ids, ["receipt-5", "receipt-4", "receipt-3", "receipt-2", "receipt-1", "receipt-0"]);
+	assert!(!is_truncated);
+}
+
+#[tokio::test]
+async fn manages_contacts() {
+	let server = MockServer::start().await.unwrap();
+	let sdk = server.sdk();
+	let contact = ContactPayload {
+		name: "Bob Builder".to_owned(),
+		address: PAYEE.to_owned(),
+		..Default::default()
+	};
+
+	let added = sdk.add_user_contacts(USER, std::slice::from_ref(&contact)).await.unwrap();
+	let uid = &added[0].uid;
+	assert_eq!(sdk.get_user_contacts_by_name(USER, "Bob Builder").await.unwrap(), added);
+
+	let updated = ContactPayload { note: Some("rent".to_owned()), ..contact };
+	sdk.update_user_contact(USER, uid, &updated).await.unwrap();
+	assert_eq!(sdk.get_user_contact_by_uid(USER, uid).await.unwrap().note.as_deref(), Some("rent"));
+
+	sdk.del_user_contact(USER, uid).await.unwrap();
+	assert!(server.state().contacts(USER).is_empty());
+	assert!(matches!(sdk.del_user_contact(USER, uid).await, Err(SdkError::NotFound { .. })));
+}
+
+#[tokio::test]
+async fn rotates_pid() {
+	let server = MockServer::start().await.unwrap();
+	let sdk = server.sdk();
+
+	let uid = sdk.get_uid(USER).await.unwrap();
+	assert_eq!(sdk.get_uid(USER).await.unwrap(), uid);
+
+	let pid = sdk.get_pid(USER).await.unwrap();
+	assert_eq!(sdk.get_pid(USER).await.unwrap(), pid);
+	sdk.fliq_notify_payer(pid.as_ref(), payment("1")).await.unwrap();
+
+	let new_pid = sdk.rotate_pid(USER).await.unwrap();
+	assert_ne!(new_pid, pid);
+	assert_eq!(sdk.get_pid(USER).await.unwrap(), new_pid);
+	assert!(matches!(
+		sdk.fliq_notify_payer(pid.as_ref(), payment("1")).await,
+		Err(SdkError::NotFound { .. })
+	));
+	sdk.fliq_notify_payer(new_pid.as_ref(), payment("1")).await.unwrap();
+}
+
+/// No balances for the coins not on the chain e.g. USDT on Sepolia.
+#[tokio::test]
+async fn rejects_unsupported_coins() {
+	let server = MockServer::start().await.unwrap();
+	let sdk = server.sdk();
+	let is_bad_request = |res: Result<_, SdkError>| matches!(res, Err(SdkError::Client { status: 400, body }) if body.message.contains("USDT"));
+
+	assert!(is_bad_request(sdk.request_faucet(USER, StableCoin::USDT, ChainName::Sepolia).await));
+	assert!(is_bad_request(
+		sdk.get_oc_chain_coin_balance(USER, ChainName::Sepolia, StableCoin::USDT)
+			.await
+			.map(|_| ())
+	));
+	let payload = PreOcpPayload { coin: StableCoin::USDT, chain: ChainName::Sepolia };
+	assert!(is_bad_request(sdk.fetch_pre_ocp_total_est_fee(USER, payload).await.map(|_| ())));
+
+	// only USDC is on Sepolia
+	sdk.request_faucet(USER, StableCoin::USDC, ChainName::Sepolia).await.unwrap();
+	let balances = sdk.get_oc_chain_all_coins_balances(USER, ChainName::Sepolia).await.unwrap();
+	assert_eq!(balances.keys().collect::<Vec<_>>(), [&StableCoin::USDC]);
+}
+
+#[test]
+#[should_panic(expected = "USDT isn't supported on Sepolia")]
+fn panics_on_unsupported_balance() {
+	unifi_mock_server::MockState::default().set_balance(
+		USER,
+		ChainName::Sepolia,
+		StableCoin::USDT,
+		"10",
+	);
+}
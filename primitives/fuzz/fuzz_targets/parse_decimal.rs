@@ -0,0 +1,25 @@
+//! Fuzz the decimal parser: never panics & roundtrips whatever it parses.
+//!
+//! ```sh
+//! cd primitives && cargo +nightly fuzz run parse_decimal
+//! ```
+
+#![no_main]
+
+use alloy_primitives::utils::format_units;
+use libfuzzer_sys::fuzz_target;
+use unifi_sdk_primitives::decimal::{DecimalParser, Rounding};
+
+fuzz_target!(|input: (u8, &str)| {
+	let (decimals, value) = input;
+	let decimals = decimals % 78;
+
+	for rounding in [Rounding::Reject, Rounding::Truncate, Rounding::HalfUp] {
+		let parser = DecimalParser::new(decimals).rounding(rounding).thousands_separator(',');
+		let Ok(parsed) = parser.parse(value) else { continue };
+
+		// formatted back (w/o any loss), it parses to the same value
+		let formatted = format_units(parsed, decimals).unwrap();
+		assert_eq!(DecimalParser::new(decimals).parse(&formatted), Ok(parsed), "{}", value);
+	}
+});
@@ -0,0 +1,314 @@
+use crate::{
+	decimal::DecimalParser,
+	errors::UfiError,
+	types::{ChainName, Mode, StableCoin},
+};
+use alloy_primitives::U256;
+use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, fmt::Display};
+
+/// Amount of a stablecoin, kept in its smallest unit (as per the coin's decimals on the chain).
+///
+/// Avoids mixing up the amount representations i.e. human format (e.g. "10.5" USDT) & raw U256
+/// (e.g. "10500000" USDT). The decimals are looked up per chain (e.g. USDT has 6 on Ethereum, but
+/// 18 on BNB Chain), so that amounts are never mis-scaled.
+///
+/// ## Example
+/// ```
+/// use unifi_sdk_primitives::{
+///     amount::Amount,
+///     types::{ChainName, StableCoin},
+/// };
+///
+/// let amount = Amount::parse("10.5", StableCoin::USDT, ChainName::Ethereum).unwrap();
+/// assert_eq!(amount.raw().to_string(), "10500000");
+/// assert_eq!(amount.to_string(), "10.500000");
+///
+/// let fee = Amount::from_raw_str("120000", StableCoin::USDT, ChainName::Ethereum).unwrap();
+/// assert_eq!(amount.checked_add(&fee).unwrap().to_string(), "10.620000");
+/// ```
+///
+/// ## Serde
+/// Serialized with both representations along with the coin & its decimals:
+/// ```json
+/// { "coin": "USDT", "decimals": 6, "raw": "10500000", "amount": "10.500000" }
+/// ```
+/// Deserialized from either `raw` or `amount` (or both, if they match).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "AmountRepr", try_from = "AmountRepr")]
+pub struct Amount {
+	raw: U256,
+	coin: StableCoin,
+	decimals: u8,
+}
+
+impl Amount {
+	/// NOTE: Errs if the coin isn't on the chain, as its decimals are unknown.
+	pub fn new(raw: U256, coin: StableCoin, chain: ChainName) -> Result<Self, UfiError> {
+		Ok(Self::with_decimals(raw, coin, coin.decimals_on(chain)?))
+	}
+
+	/// NOTE: Prefer [`Self::new`], unless the decimals are known e.g. fetched from the token
+	/// contract.
+	pub const fn with_decimals(raw: U256, coin: StableCoin, decimals: u8) -> Self {
+		Self { raw, coin, decimals }
+	}
+
+	pub fn zero(coin: StableCoin, chain: ChainName) -> Result<Self, UfiError> {
+		Self::new(U256::ZERO, coin, chain)
+	}
+
+	/// Parse human format e.g. "10.5" USDT. See [`DecimalParser`] for the accepted formats.
+	///
+	/// NOTE: Strict i.e. errs if the value has more (non-zero) decimals than the coin's, or if the
+	/// coin isn't on the chain.
+	pub fn parse(value: &str, coin: StableCoin, chain: ChainName) -> Result<Self, UfiError> {
+		Self::parse_with_decimals(value, coin, coin.decimals_on(chain)?)
+	}
+
+	fn parse_with_decimals(value: &str, coin: StableCoin, decimals: u8) -> Result<Self, UfiError> {
+		Ok(Self::with_decimals(DecimalParser::new(decimals).parse(value)?, coin, decimals))
+	}
+
+	/// Parse raw U256 str e.g. "10500000" USDT.
+	pub fn from_raw_str(raw: &str, coin: StableCoin, chain: ChainName) -> Result<Self, UfiError> {
+		Self::from_raw_str_with_decimals(raw, coin, coin.decimals_on(chain)?)
+	}
+
+	fn from_raw_str_with_decimals(
+		raw: &str,
+		coin: StableCoin,
+		decimals: u8,
+	) -> Result<Self, UfiError> {
+		let raw = raw.trim();
+		if raw.is_empty() || !raw.bytes().all(|b| b.is_ascii_digit()) {
+			return Err(UfiError::InvalidAmount(raw.to_owned()));
+		}
+
+		let raw = U256::from_str_radix(raw, 10).map_err(|_| UfiError::AmountOverflow)?;
+		Ok(Self::with_decimals(raw, coin, decimals))
+	}
+
+	/// Value in the coin's smallest unit.
+	pub const fn raw(&self) -> U256 {
+		self.raw
+	}
+
+	pub const fn coin(&self) -> StableCoin {
+		self.coin
+	}
+
+	pub const fn decimals(&self) -> u8 {
+		self.decimals
+	}
+
+	pub fn is_zero(&self) -> bool {
+		self.raw.is_zero()
+	}
+
+	pub fn checked_add(&self, other: &Self) -> Result<Self, UfiError> {
+		self.ensure_same_coin(other)?;
+		let raw = self.raw.checked_add(other.raw).ok_or(UfiError::AmountOverflow)?;
+		Ok(Self { raw, ..*self })
+	}
+
+	pub fn checked_sub(&self, other: &Self) -> Result<Self, UfiError> {
+		self.ensure_same_coin(other)?;
+		let raw = self.raw.checked_sub(other.raw).ok_or(UfiError::NegativeAmount)?;
+		Ok(Self { raw, ..*self })
+	}
+
+	/// `self - other`, floored at zero.
+	pub fn saturating_sub(&self, other: &Self) -> Result<Self, UfiError> {
+		self.ensure_same_coin(other)?;
+		Ok(Self { raw: self.raw.saturating_sub(other.raw), ..*self })
+	}
+
+	/// Same coin with the same decimals.
+	fn ensure_same_coin(&self, other: &Self) -> Result<(), UfiError> {
+		if self.coin != other.coin {
+			return Err(UfiError::CoinMismatch(self.coin, other.coin));
+		}
+		if self.decimals != other.decimals {
+			return Err(UfiError::DecimalsMismatch(self.decimals, other.decimals));
+		}
+		Ok(())
+	}
+}
+
+/// Amounts of diff. coins (or decimals) aren't comparable.
+impl PartialOrd for Amount {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		self.ensure_same_coin(other).ok().map(|_| self.raw.cmp(&other.raw))
+	}
+}
+
+/// Human format with all the coin's decimals. E.g. "10.500000" USDT
+impl Display for Amount {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let decimals = self.decimals as usize;
+		let digits = format!("{:0>width$}", self.raw.to_string(), width = decimals + 1);
+		let (whole, frac) = digits.split_at(digits.len() - decimals);
+		if frac.is_empty() { write!(f, "{}", whole) } else { write!(f, "{}.{}", whole, frac) }
+	}
+}
+
+#[derive(Serialize, Deserialize)]
+struct AmountRepr {
+	coin: StableCoin,
+	decimals: u8,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	raw: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	amount: Option<String>,
+}
+
+impl From<Amount> for AmountRepr {
+	fn from(val: Amount) -> Self {
+		Self {
+			coin: val.coin,
+			decimals: val.decimals,
+			raw: Some(val.raw.to_string()),
+			amount: Some(val.to_string()),
+		}
+	}
+}
+
+impl TryFrom<AmountRepr> for Amount {
+	type Error = UfiError;
+
+	fn try_from(val: AmountRepr) -> Result<Self, Self::Error> {
+		let AmountRepr { coin, decimals, raw, amount } = val;
+		// The chain isn't known, so the decimals must be the coin's on any of the chains.
+		let is_known = ChainName::all(Mode::Test)
+			.iter()
+			.any(|&chain| coin.decimals_on(chain) == Ok(decimals));
+		if !is_known {
+			return Err(UfiError::InvalidAmount(format!("{} decimals for {}", decimals, coin)));
+		}
+		let raw = raw
+			.map(|raw| Amount::from_raw_str_with_decimals(&raw, coin, decimals))
+			.transpose()?;
+		let amount = amount
+			.map(|amount| Amount::parse_with_decimals(&amount, coin, decimals))
+			.transpose()?;
+
+		match (raw, amount) {
+			(Some(raw), Some(amount)) if raw != amount =>
+				Err(UfiError::InvalidAmount(format!("raw: {} != amount: {}", raw.raw, amount))),
+			(Some(amount), _) | (None, Some(amount)) => Ok(amount),
+			(None, None) => Err(UfiError::InvalidAmount("missing `raw` or `amount`".to_owned())),
+		}
+	}
+}
+
+/// Test
+/// ```sh
+/// RUSTFLAGS="-Awarnings" cargo t -p unifi-sdk-primitives -F utils -- amount::tests --show-output
+/// ```
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_and_displays() {
+		let amount = Amount::parse("10.5", StableCoin::USDT, ChainName::Ethereum).unwrap();
+		assert_eq!(amount.raw(), U256::from(10_500_000));
+		assert_eq!(amount.to_string(), "10.500000");
+		assert_eq!(
+			Amount::parse(".5", StableCoin::USDC, ChainName::Ethereum).unwrap().to_string(),
+			"0.500000"
+		);
+		assert_eq!(
+			Amount::parse("7", StableCoin::DAI, ChainName::Ethereum)
+				.unwrap()
+				.raw()
+				.to_string(),
+			"7".to_owned() + &"0".repeat(18)
+		);
+		assert_eq!(
+			Amount::from_raw_str("1", StableCoin::USDT, ChainName::Ethereum)
+				.unwrap()
+				.to_string(),
+			"0.000001"
+		);
+
+		assert!(matches!(
+			Amount::parse("1.1234567", StableCoin::USDT, ChainName::Ethereum),
+			Err(UfiError::MaxDecimalsReached(6))
+		));
+		for invalid in ["", ".", "-1", "1e-7", "1.2.3", "1,000", "abc"] {
+			assert!(
+				Amount::parse(invalid, StableCoin::USDT, ChainName::Ethereum).is_err(),
+				"{}",
+				invalid
+			);
+		}
+		assert!(Amount::from_raw_str("1.5", StableCoin::USDT, ChainName::Ethereum).is_err());
+
+		// USDC isn't on opBNB
+		let not_found = Err(UfiError::TokenNotFound(StableCoin::USDC, ChainName::OpBNB));
+		assert_eq!(Amount::parse("1", StableCoin::USDC, ChainName::OpBNB), not_found);
+		assert_eq!(Amount::from_raw_str("1", StableCoin::USDC, ChainName::OpBNB), not_found);
+		assert_eq!(Amount::new(U256::ONE, StableCoin::USDC, ChainName::OpBNB), not_found);
+	}
+
+	#[test]
+	fn checked_arithmetic() {
+		let usdt = |v| Amount::parse(v, StableCoin::USDT, ChainName::Ethereum).unwrap();
+
+		assert_eq!(usdt("1.5").checked_add(&usdt("2")).unwrap(), usdt("3.5"));
+		assert_eq!(usdt("2").checked_sub(&usdt("0.5")).unwrap(), usdt("1.5"));
+		assert!(matches!(usdt("1").checked_sub(&usdt("2")), Err(UfiError::NegativeAmount)));
+		assert!(usdt("1").saturating_sub(&usdt("2")).unwrap().is_zero());
+		assert!(matches!(
+			Amount::new(U256::MAX, StableCoin::USDT, ChainName::Ethereum)
+				.unwrap()
+				.checked_add(&usdt("1")),
+			Err(UfiError::AmountOverflow)
+		));
+
+		let usdc = Amount::parse("1", StableCoin::USDC, ChainName::Ethereum).unwrap();
+		assert!(matches!(usdt("1").checked_add(&usdc), Err(UfiError::CoinMismatch(..))));
+		assert!(usdt("1") < usdt("1.1"));
+		assert_eq!(usdt("1").partial_cmp(&usdc), None);
+
+		// USDT has 18 decimals on BNB Chain
+		let bnb_usdt = Amount::parse("1", StableCoin::USDT, ChainName::BnbChain).unwrap();
+		assert_eq!(bnb_usdt.raw(), U256::from(10).pow(U256::from(18)));
+		assert_eq!(bnb_usdt.to_string(), "1.000000000000000000");
+		assert!(matches!(usdt("1").checked_add(&bnb_usdt), Err(UfiError::DecimalsMismatch(6, 18))));
+		assert_eq!(usdt("1").partial_cmp(&bnb_usdt), None);
+	}
+
+	#[test]
+	fn serde_both_representations() {
+		let amount = Amount::parse("10.5", StableCoin::USDC, ChainName::Ethereum).unwrap();
+		let json = serde_json::to_string(&amount).unwrap();
+		assert_eq!(json, r#"{"coin":"USDC","decimals":6,"raw":"10500000","amount":"10.500000"}"#);
+		assert_eq!(serde_json::from_str::<Amount>(&json).unwrap(), amount);
+
+		let from_raw = r#"{"coin":"USDC","decimals":6,"raw":"10500000"}"#;
+		assert_eq!(serde_json::from_str::<Amount>(from_raw).unwrap(), amount);
+		let from_human = r#"{"coin":"USDC","decimals":6,"amount":"10.5"}"#;
+		assert_eq!(serde_json::from_str::<Amount>(from_human).unwrap(), amount);
+
+		assert!(
+			serde_json::from_str::<Amount>(
+				r#"{"coin":"USDC","decimals":6,"raw":"1","amount":"1"}"#
+			)
+			.is_err()
+		);
+		assert!(serde_json::from_str::<Amount>(r#"{"coin":"USDC","decimals":6}"#).is_err());
+		assert!(serde_json::from_str::<Amount>(r#"{"coin":"USDC","amount":"1"}"#).is_err());
+		// USDC has 6 (or 18 on BNB Chain) decimals
+		assert!(
+			serde_json::from_str::<Amount>(r#"{"coin":"USDC","decimals":8,"amount":"1"}"#).is_err()
+		);
+		let bnb_usdc = r#"{"coin":"USDC","decimals":18,"amount":"1"}"#;
+		assert_eq!(
+			serde_json::from_str::<Amount>(bnb_usdc).unwrap(),
+			Amount::parse("1", StableCoin::USDC, ChainName::BnbChain).unwrap()
+		);
+	}
+}
@@ -0,0 +1,389 @@
+use crate::errors::UfiError;
+use alloy_primitives::U256;
+
+/// Max. no. of digits in a U256 value.
+const MAX_U256_DIGITS: usize = 78;
+
+/// Max. no. of (whole & fractional) digits in the mantissa. Way beyond what a U256 can hold, but
+/// bounds how far the digits can offset the exponent.
+const MAX_MANTISSA_DIGITS: usize = 500;
+
+/// Bound on the exponent in scientific notation, so that the shift never overflows.
+///
+/// NOTE: Clamping to it doesn't change the result, as it exceeds [`MAX_MANTISSA_DIGITS`] by more
+/// than [`MAX_U256_DIGITS`] & the max. decimals (u8) i.e. the value overflows (or rounds to zero)
+/// either way.
+const MAX_EXPONENT: i64 = 1_000;
+
+/// What to do with the decimals beyond the coin's decimals.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Rounding {
+	/// Err as [`UfiError::MaxDecimalsReached`]. Trailing zeros are fine. E.g. "1.1234560" USDT.
+	#[default]
+	Reject,
+	/// Drop the excess decimals. E.g. "1.1234569" -> "1.123456" USDT.
+	Truncate,
+	/// Round half away from zero. E.g. "1.1234565" -> "1.123457" USDT.
+	HalfUp,
+}
+
+/// Strict parser of human format decimal str to U256 (in the coin's smallest unit).
+///
+/// ## Accepts
+/// - surrounding whitespace: " 10.5 "
+/// - leading `+`: "+10.5"
+/// - no whole or fractional digits: ".5", "5."
+/// - scientific notation: "1.05e1", "0E-18", "5E+2"
+/// - thousands separators (opt-in): "1,000,000.5"
+///
+/// Anything else (e.g. "1.2.3", "1e", "0x10", "1_000") errs with a precise [`UfiError`].
+///
+/// ## Example
+/// ```
+/// use alloy_primitives::U256;
+/// use unifi_sdk_primitives::decimal::{DecimalParser, Rounding};
+///
+/// let usdt = DecimalParser::new(6);
+/// assert_eq!(usdt.parse(" +10.5 ").unwrap(), U256::from(10_500_000));
+/// assert_eq!(usdt.parse("1.05e1").unwrap(), U256::from(10_500_000));
+/// assert!(usdt.parse("1.1234567").is_err());
+///
+/// let usdt = usdt.rounding(Rounding::HalfUp).thousands_separator(',');
+/// assert_eq!(usdt.parse("1,000.1234565").unwrap(), U256::from(1_000_123_457));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecimalParser {
+	decimals: u8,
+	rounding: Rounding,
+	thousands_separator: Option<char>,
+}
+
+impl DecimalParser {
+	/// Parser for a coin with `decimals`, rejecting excess decimals & thousands separators.
+	pub const fn new(decimals: u8) -> Self {
+		Self { decimals, rounding: Rounding::Reject, thousands_separator: None }
+	}
+
+	pub const fn rounding(mut self, rounding: Rounding) -> Self {
+		self.rounding = rounding;
+		self
+	}
+
+	/// Allow the `separator` between the groups of 3 whole digits. E.g. ',' for "1,000,000".
+	///
+	/// NOTE: The separator can't be a digit, '.', '+', '-', 'e' or 'E'.
+	pub const fn thousands_separator(mut self, separator: char) -> Self {
+		self.thousands_separator = Some(separator);
+		self
+	}
+
+	pub fn parse(&self, value: &str) -> Result<U256, UfiError> {
+		let offset = value.len() - value.trim_start().len();
+		let trimmed = value.trim();
+		if trimmed.is_empty() {
+			return Err(UfiError::EmptyAmount);
+		}
+
+		let (unsigned, offset) = match trimmed.as_bytes()[0] {
+			b'+' => (&trimmed[1..], offset + 1),
+			b'-' => return Err(UfiError::NegativeAmount),
+			_ => (trimmed, offset),
+		};
+		let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+			Some(idx) => (&unsigned[..idx], Some(&unsigned[idx + 1..])),
+			None => (unsigned, None),
+		};
+
+		let (whole, frac) = self.split_mantissa(mantissa, offset)?;
+		if whole.is_empty() && frac.is_empty() {
+			return Err(UfiError::InvalidAmount(value.to_owned()));
+		}
+		if whole.len() + frac.len() > MAX_MANTISSA_DIGITS {
+			return Err(UfiError::InvalidAmount(format!(
+				"more than {} digits",
+				MAX_MANTISSA_DIGITS
+			)));
+		}
+		let exponent = match exponent {
+			Some(exponent) => parse_exponent(exponent)?,
+			None => 0,
+		};
+
+		let digits = whole + &frac;
+		let digits = digits.trim_start_matches('0');
+		if digits.is_empty() {
+			return Ok(U256::ZERO);
+		}
+
+		// value = digits * 10^shift (in the smallest unit)
+		let shift = self.decimals as i64 + exponent - frac.len() as i64;
+		if shift >= 0 {
+			if digits.len() as i64 + shift > MAX_U256_DIGITS as i64 {
+				return Err(UfiError::AmountOverflow);
+			}
+			let digits = format!("{}{}", digits, "0".repeat(shift as usize));
+			return U256::from_str_radix(&digits, 10).map_err(|_| UfiError::AmountOverflow);
+		}
+
+		let excess = shift.unsigned_abs() as usize;
+		let (kept, dropped) = digits.split_at(digits.len().saturating_sub(excess));
+		// dropped digits are non-zero as the digits have no leading zeros, unless all are dropped
+		let dropped_non_zero = !dropped.trim_end_matches('0').is_empty();
+		if kept.len() > MAX_U256_DIGITS {
+			return Err(UfiError::AmountOverflow);
+		}
+		let kept = if kept.is_empty() {
+			U256::ZERO
+		} else {
+			U256::from_str_radix(kept, 10).map_err(|_| UfiError::AmountOverflow)?
+		};
+
+		match self.rounding {
+			Rounding::Reject if dropped_non_zero =>
+				Err(UfiError::MaxDecimalsReached(self.decimals)),
+			Rounding::Reject | Rounding::Truncate => Ok(kept),
+			Rounding::HalfUp => {
+				// Only the 1st dropped digit matters, if it's in place (not shifted out as zero).
+				let round_up = excess <= digits.len() && dropped.as_bytes()[0] >= b'5';
+				if round_up {
+					kept.checked_add(U256::ONE).ok_or(UfiError::AmountOverflow)
+				} else {
+					Ok(kept)
+				}
+			},
+		}
+	}
+
+	/// Split into (whole, fractional) digits, validating the chars & thousands separators.
+	fn split_mantissa(&self, mantissa: &str, offset: usize) -> Result<(String, String), UfiError> {
+		let mut whole = String::with_capacity(mantissa.len());
+		let mut frac = String::new();
+		let mut point = None;
+		// position of the last thousands separator
+		let mut separator = None;
+		let mut group_digits = 0;
+
+		for (idx, ch) in mantissa.char_indices() {
+			let pos = offset + idx;
+			match ch {
+				'0'..='9' if point.is_some() => frac.push(ch),
+				'0'..='9' => {
+					whole.push(ch);
+					group_digits += 1;
+				},
+				'.' if point.is_none() => {
+					point = Some(pos);
+					check_last_group(separator, group_digits)?;
+				},
+				_ if Some(ch) == self.thousands_separator && point.is_none() => {
+					// 1st group has 1-3 digits, the rest exactly 3
+					let group_ok = match separator {
+						None => (1..=3).contains(&group_digits),
+						Some(_) => group_digits == 3,
+					};
+					if !group_ok {
+						return Err(UfiError::MisplacedThousandsSeparator(pos));
+					}
+					separator = Some(pos);
+					group_digits = 0;
+				},
+				_ => return Err(UfiError::InvalidChar(ch, pos)),
+			}
+		}
+		if point.is_none() {
+			check_last_group(separator, group_digits)?;
+		}
+
+		Ok((whole, frac))
+	}
+}
+
+/// The group after the last thousands separator must have exactly 3 digits.
+fn check_last_group(separator: Option<usize>, group_digits: usize) -> Result<(), UfiError> {
+	match separator {
+		Some(pos) if group_digits != 3 => Err(UfiError::MisplacedThousandsSeparator(pos)),
+		_ => Ok(()),
+	}
+}
+
+/// Parse the exponent (after 'e'/'E') clamped to ±[`MAX_EXPONENT`].
+fn parse_exponent(exponent: &str) -> Result<i64, UfiError> {
+	let invalid = || UfiError::InvalidExponent(exponent.to_owned());
+	let (negative, digits) = match exponent.as_bytes().first() {
+		Some(b'+') => (false, &exponent[1..]),
+		Some(b'-') => (true, &exponent[1..]),
+		_ => (false, exponent),
+	};
+	if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+		return Err(invalid());
+	}
+
+	let magnitude = digits
+		.bytes()
+		.fold(0i64, |acc, b| (acc * 10 + (b - b'0') as i64).min(MAX_EXPONENT));
+	Ok(if negative { -magnitude } else { magnitude })
+}
+
+/// Test
+/// ```sh
+/// RUSTFLAGS="-Awarnings" cargo t -p unifi-sdk-primitives -F utils -- decimal::tests --show-output
+/// ```
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use alloy_primitives::utils::format_units;
+	use proptest::prelude::*;
+
+	fn usdt() -> DecimalParser {
+		DecimalParser::new(6)
+	}
+
+	#[test]
+	fn parses_valid_inputs() {
+		let cases = [
+			("10.5", 10_500_000u64),
+			(" \t+10.5\n", 10_500_000),
+			(".5", 500_000),
+			("5.", 5_000_000),
+			("007.000001", 7_000_001),
+			("1.1234560", 1_123_456),
+			("1.05e1", 10_500_000),
+			("5E+2", 500_000_000),
+			("123456e-6", 123_456),
+			("1e-6", 1),
+			("0E-18", 0),
+			("0.000000000000000000000e50", 0),
+			("0", 0),
+		];
+		for (value, expected) in cases {
+			assert_eq!(usdt().parse(value).unwrap(), U256::from(expected), "{}", value);
+		}
+
+		let dai = DecimalParser::new(18);
+		assert_eq!(dai.parse("0E-18").unwrap(), U256::ZERO);
+		assert_eq!(dai.parse("1e-18").unwrap(), U256::ONE);
+	}
+
+	#[test]
+	fn rejects_invalid_inputs() {
+		let cases = [
+			("", UfiError::EmptyAmount),
+			("   ", UfiError::EmptyAmount),
+			("-1", UfiError::NegativeAmount),
+			(".", UfiError::InvalidAmount(".".to_owned())),
+			("+", UfiError::InvalidAmount("+".to_owned())),
+			("e5", UfiError::InvalidAmount("e5".to_owned())),
+			("1.2.3", UfiError::InvalidChar('.', 3)),
+			(" 1,000", UfiError::InvalidChar(',', 2)),
+			("1 000", UfiError::InvalidChar(' ', 1)),
+			("++1", UfiError::InvalidChar('+', 1)),
+			("0x10", UfiError::InvalidChar('x', 1)),
+			("1e", UfiError::InvalidExponent("".to_owned())),
+			("1e+", UfiError::InvalidExponent("+".to_owned())),
+			("1e1.5", UfiError::InvalidExponent("1.5".to_owned())),
+			("1.1234567", UfiError::MaxDecimalsReached(6)),
+			("1e-7", UfiError::MaxDecimalsReached(6)),
+			("1e72", UfiError::AmountOverflow),
+			("1e999999999999999999999", UfiError::AmountOverflow),
+		];
+		for (value, expected) in cases {
+			assert_eq!(usdt().parse(value).unwrap_err(), expected, "{}", value);
+		}
+		assert_eq!(usdt().parse(&"9".repeat(73)).unwrap_err(), UfiError::AmountOverflow);
+
+		// a long mantissa would offset the clamped exponent e.g. 10^600 * 10^-600
+		let too_long = UfiError::InvalidAmount(format!("more than {} digits", MAX_MANTISSA_DIGITS));
+		assert_eq!(usdt().parse(&format!("1{}e-600", "0".repeat(600))).unwrap_err(), too_long);
+		assert_eq!(usdt().parse(&format!("0.{}1e600", "0".repeat(599))).unwrap_err(), too_long);
+		assert_eq!(usdt().parse(&"0".repeat(MAX_MANTISSA_DIGITS)).unwrap(), U256::ZERO);
+		assert_eq!(
+			usdt().parse(&format!("1{}e-494", "0".repeat(494))).unwrap(),
+			U256::from(1_000_000)
+		);
+	}
+
+	#[test]
+	fn parses_thousands_separators() {
+		let parser = usdt().thousands_separator(',');
+		assert_eq!(parser.parse("1,000,000.5").unwrap(), U256::from(1_000_000_500_000u64));
+		assert_eq!(parser.parse("999").unwrap(), U256::from(999_000_000));
+		assert_eq!(parser.parse("1,000e3").unwrap(), U256::from(1_000_000_000_000u64));
+
+		let cases = [
+			(",100", 0),
+			("1000,000", 4),
+			("1,00", 1),
+			("1,0000", 1),
+			("1,000,00.5", 5),
+			("1,,000", 2),
+			("1,000.000,1", 9),
+		];
+		for (value, pos) in cases {
+			let err = parser.parse(value).unwrap_err();
+			assert!(
+				matches!(err, UfiError::MisplacedThousandsSeparator(p) | UfiError::InvalidChar(',', p) if p == pos),
+				"{}: {:?}",
+				value,
+				err
+			);
+		}
+	}
+
+	#[test]
+	fn rounds() {
+		let truncate = usdt().rounding(Rounding::Truncate);
+		let half_up = usdt().rounding(Rounding::HalfUp);
+
+		assert_eq!(truncate.parse("1.1234569").unwrap(), U256::from(1_123_456));
+		assert_eq!(half_up.parse("1.1234565").unwrap(), U256::from(1_123_457));
+		assert_eq!(half_up.parse("1.1234564999").unwrap(), U256::from(1_123_456));
+		assert_eq!(half_up.parse("0.0000005").unwrap(), U256::ONE);
+		assert_eq!(half_up.parse("0.00000049").unwrap(), U256::ZERO);
+		assert_eq!(half_up.parse("5e-7").unwrap(), U256::ONE);
+		assert_eq!(half_up.parse("5e-8").unwrap(), U256::ZERO);
+		assert_eq!(truncate.parse("1e-1000000").unwrap(), U256::ZERO);
+	}
+
+	proptest! {
+		#[test]
+		fn roundtrips_formatted(raw in any::<u128>(), decimals in 0u8..=18) {
+			let value = U256::from(raw);
+			let formatted = format_units(value, decimals).unwrap();
+			prop_assert_eq!(DecimalParser::new(decimals).parse(&formatted).unwrap(), value);
+		}
+
+		#[test]
+		fn scientific_matches_plain(mantissa in 0u64..u64::MAX, exponent in -30i64..30) {
+			let parser = DecimalParser::new(18).rounding(Rounding::Truncate);
+			let plain = if exponent >= 0 {
+				format!("{}{}", mantissa, "0".repeat(exponent as usize))
+			} else {
+				let digits = format!("{:0>width$}", mantissa, width = exponent.unsigned_abs() as usize + 1);
+				let (whole, frac) = digits.split_at(digits.len() - exponent.unsigned_abs() as usize);
+				format!("{}.{}", whole, frac)
+			};
+			prop_assert_eq!(
+				parser.parse(&format!("{}e{}", mantissa, exponent)).unwrap(),
+				parser.parse(&plain).unwrap()
+			);
+		}
+
+		#[test]
+		fn rounding_brackets_truncation(value in "[0-9]{1,20}\\.[0-9]{0,30}") {
+			let truncated = usdt().rounding(Rounding::Truncate).parse(&value).unwrap();
+			let rounded = usdt().rounding(Rounding::HalfUp).parse(&value).unwrap();
+			prop_assert!(rounded == truncated || rounded == truncated + U256::ONE);
+			if let Ok(strict) = usdt().parse(&value) {
+				prop_assert_eq!(strict, truncated);
+				prop_assert_eq!(rounded, truncated);
+			}
+		}
+
+		#[test]
+		fn never_panics(value in "\\PC*", decimals in any::<u8>()) {
+			for rounding in [Rounding::Reject, Rounding::Truncate, Rounding::HalfUp] {
+				let _ = DecimalParser::new(decimals).rounding(rounding).thousands_separator(',').parse(&value);
+			}
+		}
+	}
+}
@@ -1,7 +1,9 @@
+use crate::types::{ChainName, StableCoin};
+use alloy_primitives::{Address, U256};
 use thiserror::Error as ThisError;
 
 #[allow(dead_code)]
-#[derive(ThisError, Debug)]
+#[derive(ThisError, Debug, PartialEq, Eq)]
 pub enum UfiError {
 	#[error("Max. {0} decimal places allowed \nPlease enter a valid amount.")]
 	MaxDecimalsReached(u8),
@@ -9,4 +11,58 @@ pub enum UfiError {
 	InsufficientBalance,
 	#[error("Amount can't be zero. \nPlease enter a valid amount to proceed.")]
 	ZeroAmount,
+	#[error("Invalid amount: {0} \nPlease enter a valid amount.")]
+	InvalidAmount(String),
+	#[error("Amount can't be empty. \nPlease enter a valid amount.")]
+	EmptyAmount,
+	#[error("Invalid character '{0}' at position {1}. \nPlease enter a valid amount.")]
+	InvalidChar(char, usize),
+	#[error("Invalid exponent: '{0}'. \nPlease enter a valid amount.")]
+	InvalidExponent(String),
+	#[error("Misplaced thousands separator at position {0}. \nPlease enter a valid amount.")]
+	MisplacedThousandsSeparator(usize),
+	#[error("Amount too large. \nPlease enter a valid amount.")]
+	AmountOverflow,
+	#[error("Amount can't be negative.")]
+	NegativeAmount,
+	#[error("Can't mix {0} & {1} amounts.")]
+	CoinMismatch(StableCoin, StableCoin),
+	#[error("Can't mix amounts with {0} & {1} decimals.")]
+	DecimalsMismatch(u8, u8),
+	#[error("Custom tokens can't be registered on {0}. Only on Anvil & testnets.")]
+	TokenRegistrationNotAllowed(ChainName),
+	#[error("Token {0} is already registered as {1} on {2}.")]
+	TokenAddressTaken(Address, StableCoin, ChainName),
+	#[error("Address can't be zero.")]
+	ZeroAddress,
+	#[error("Invalid private key.")]
+	InvalidPrivateKey,
+	#[error("Signing failed: {0}")]
+	SigningFailed(String),
+	#[error("Invalid signature length: {0}. Expected 64 or 65 bytes.")]
+	InvalidSignatureLength(usize),
+	#[error("Invalid signature.")]
+	InvalidSignature,
+	#[error("Invalid signer. Expected: {0}, recovered: {1}.")]
+	InvalidSigner(Address, Address),
+	#[error("Permit expired at (deadline): {0}.")]
+	SignatureExpired(U256),
+	#[error("Permit has no tokens.")]
+	EmptyPermit,
+	#[error("Permit nonce not set.")]
+	MissingPermitNonce,
+	#[error("Can't mix {0} & {1} payments in a permit.")]
+	ChainMismatch(ChainName, ChainName),
+	#[error("{0} isn't supported on {1}.")]
+	TokenNotFound(StableCoin, ChainName),
+	#[error("Invalid typed data: {0}")]
+	TypedData(String),
+	#[error("Length mismatch: {0} != {1}.")]
+	LengthMismatch(usize, usize),
+	#[error("Batch has no payments.")]
+	EmptyBatch,
+	#[error("Requested amount: {0} exceeds the permitted: {1}.")]
+	PermitAmountExceeded(U256, U256),
+	#[error("Invalid calldata: {0}")]
+	InvalidCalldata(String),
 }
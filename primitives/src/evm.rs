@@ -1,5 +1,14 @@
-use alloy_primitives::{Address, U256, hex::ToHexExt};
-use alloy_sol_types::{SolCall, sol};
+use crate::{
+	amount::Amount,
+	errors::UfiError,
+	permit2::{
+		BundlePayV2::{self, BundlePayV2Calls, BundlePayV2Errors},
+		ISignatureTransfer, PermitBatchTransferFrom,
+	},
+	types::{ChainName, StableCoin},
+};
+use alloy_primitives::{Address, Bytes, U256, hex::ToHexExt};
+use alloy_sol_types::{SolCall, SolInterface, sol};
 
 sol! {
 	interface IERC20 {
@@ -14,6 +23,654 @@ pub fn calldata_approve(spender: Address, amount: U256) -> Vec<u8> {
 	call.abi_encode()
 }
 
+/// Gas charged for the calldata (EIP-2028) i.e. 4 per zero & 16 per non-zero byte.
+///
+/// ## Usage
+/// - L1 data fee of a rollup tx.
+pub fn calldata_gas(data: &[u8]) -> u64 {
+	data.iter().map(|&b| if b == 0 { 4 } else { 16 }).sum()
+}
+
 pub fn get_data_hex(data: Vec<u8>) -> String {
 	format!("0x{}", data.encode_hex())
 }
+
+/// NC payment i.e. transfers from the payer via the Permit2 signature, to be batched via
+/// [`calldata_batch_payment_ss_permit`] or [`calldata_batch_payment_msmt`].
+///
+/// - `transfer_details`: (to, requested amount) per token of the permit (in the same order).
+/// - `signature`: of the permit by `from` e.g. via [`crate::signer::Signer::sign_permit`].
+pub fn permit_payment(
+	from: Address,
+	permit: PermitBatchTransferFrom,
+	transfer_details: &[(Address, U256)],
+	signature: &[u8],
+) -> Result<BundlePayV2::BatchPaymentSSPermit, UfiError> {
+	let pay = BundlePayV2::BatchPaymentSSPermit {
+		from,
+		permit: permit.into(),
+		transferDetails: transfer_details
+			.iter()
+			.map(|&(to, requested_amount)| ISignatureTransfer::SignatureTransferDetails {
+				to,
+				requestedAmount: requested_amount,
+			})
+			.collect(),
+		signature: Bytes::copy_from_slice(signature),
+	};
+	validate_permit_payment(&pay)?;
+	Ok(pay)
+}
+
+/// Validate the NC payment as Permit2 (or `BundlePayV2`) would, to fail before sending the tx.
+///
+/// ## Usage
+/// - NC payments not built via [`permit_payment`] e.g. deserialized ones.
+pub fn validate_permit_payment(pay: &BundlePayV2::BatchPaymentSSPermit) -> Result<(), UfiError> {
+	ensure_non_zero(&[pay.from])?;
+	if pay.permit.permitted.is_empty() {
+		return Err(UfiError::EmptyPermit);
+	}
+	ensure_same_len(pay.permit.permitted.len(), pay.transferDetails.len())?;
+	if !matches!(pay.signature.len(), 64 | 65) {
+		return Err(UfiError::InvalidSignatureLength(pay.signature.len()));
+	}
+
+	for (permitted, details) in pay.permit.permitted.iter().zip(&pay.transferDetails) {
+		ensure_non_zero(&[permitted.token, details.to])?;
+		// Permit2 reverts with `InvalidAmount`
+		if details.requestedAmount > permitted.amount {
+			return Err(UfiError::PermitAmountExceeded(details.requestedAmount, permitted.amount));
+		}
+	}
+	Ok(())
+}
+
+/// Get the calldata (as hex) for `BundlePayV2.batchPaymentMSMT` i.e. multiple senders, multiple
+/// tokens.
+///
+/// - `froms`, `tos`, `tokens` & `amounts`: custodial payments, index-wise.
+/// - `payments_nc`: NC payments. See [`permit_payment`].
+pub fn calldata_batch_payment_msmt(
+	relayer_index: u16,
+	froms: &[Address],
+	tos: &[Address],
+	tokens: &[Address],
+	amounts: &[U256],
+	payments_nc: Vec<BundlePayV2::BatchPaymentSSPermit>,
+) -> Result<String, UfiError> {
+	ensure_same_len(froms.len(), tos.len())?;
+	ensure_same_len(froms.len(), tokens.len())?;
+	ensure_same_len(froms.len(), amounts.len())?;
+	if froms.is_empty() && payments_nc.is_empty() {
+		return Err(UfiError::EmptyBatch);
+	}
+	ensure_non_zero(froms)?;
+	ensure_non_zero(tos)?;
+	ensure_non_zero(tokens)?;
+	payments_nc.iter().try_for_each(validate_permit_payment)?;
+
+	let call = BundlePayV2::batchPaymentMSMTCall {
+		relayerIndex: relayer_index,
+		froms: froms.to_vec(),
+		tos: tos.to_vec(),
+		tokens: tokens.to_vec(),
+		amounts: amounts.to_vec(),
+		paymentsNC: payments_nc,
+	};
+	Ok(get_data_hex(call.abi_encode()))
+}
+
+/// Get the calldata (as hex) for `BundlePayV2.batchPaymentSSMT` i.e. single sender, multiple
+/// tokens.
+///
+/// - `tokens`, `tos` & `amounts`: payments, index-wise.
+pub fn calldata_batch_payment_ssmt(
+	relayer_index: u16,
+	from: Address,
+	tokens: &[Address],
+	tos: &[Address],
+	amounts: &[U256],
+) -> Result<String, UfiError> {
+	ensure_same_len(tokens.len(), tos.len())?;
+	ensure_same_len(tokens.len(), amounts.len())?;
+	if tokens.is_empty() {
+		return Err(UfiError::EmptyBatch);
+	}
+	ensure_non_zero(&[from])?;
+	ensure_non_zero(tokens)?;
+	ensure_non_zero(tos)?;
+
+	let call = BundlePayV2::batchPaymentSSMTCall {
+		relayerIndex: relayer_index,
+		from,
+		tokens: tokens.to_vec(),
+		tos: tos.to_vec(),
+		amounts: amounts.to_vec(),
+	};
+	Ok(get_data_hex(call.abi_encode()))
+}
+
+/// Get the calldata (as hex) for `BundlePayV2.batchPaymentSSST` i.e. single sender, single token.
+///
+/// - `tos` & `amounts`: payments, index-wise.
+pub fn calldata_batch_payment_ssst(
+	relayer_index: u16,
+	from: Address,
+	token: Address,
+	tos: &[Address],
+	amounts: &[U256],
+) -> Result<String, UfiError> {
+	ensure_same_len(tos.len(), amounts.len())?;
+	if tos.is_empty() {
+		return Err(UfiError::EmptyBatch);
+	}
+	ensure_non_zero(&[from, token])?;
+	ensure_non_zero(tos)?;
+
+	let call = BundlePayV2::batchPaymentSSSTCall {
+		relayerIndex: relayer_index,
+		from,
+		token,
+		tos: tos.to_vec(),
+		amounts: amounts.to_vec(),
+	};
+	Ok(get_data_hex(call.abi_encode()))
+}
+
+/// Get the calldata (as hex) for `BundlePayV2.batchPaymentSSPermit` i.e. single sender via the
+/// Permit2 signature. See [`permit_payment`].
+pub fn calldata_batch_payment_ss_permit(
+	relayer_index: u16,
+	pay: BundlePayV2::BatchPaymentSSPermit,
+) -> Result<String, UfiError> {
+	validate_permit_payment(&pay)?;
+
+	let call = BundlePayV2::batchPaymentSSPermitCall { relayerIndex: relayer_index, pay };
+	Ok(get_data_hex(call.abi_encode()))
+}
+
+/// Get the calldata (as hex) for `BundlePayV2.singlePaymentSSSTSR` i.e. single sender, single
+/// token, single receiver.
+pub fn calldata_single_payment_ssstsr(
+	relayer_index: u16,
+	from: Address,
+	to: Address,
+	token: Address,
+	amount: U256,
+) -> Result<String, UfiError> {
+	ensure_non_zero(&[from, to, token])?;
+
+	let call = BundlePayV2::singlePaymentSSSTSRCall {
+		relayerIndex: relayer_index,
+		from,
+		to,
+		token,
+		amount,
+	};
+	Ok(get_data_hex(call.abi_encode()))
+}
+
+/// Token transfer decoded from the BundlePayV2 calldata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Payment {
+	pub from: Address,
+	pub to: Address,
+	pub token: Address,
+	/// `None` if the token isn't a (registered) stablecoin on the chain.
+	pub coin: Option<StableCoin>,
+	/// In the token's smallest unit.
+	pub amount: U256,
+}
+
+impl Payment {
+	/// Amount of the coin, if the token is a stablecoin.
+	pub fn coin_amount(&self, chain: ChainName) -> Option<Amount> {
+		self.coin.and_then(|coin| Amount::new(self.amount, coin, chain).ok())
+	}
+}
+
+/// NC payment (via the Permit2 signature) decoded from the BundlePayV2 calldata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermitPayment {
+	pub from: Address,
+	pub nonce: U256,
+	pub deadline: U256,
+	pub signature: Bytes,
+	/// Transfers of the requested amounts of the permitted tokens.
+	pub payments: Vec<Payment>,
+}
+
+/// BundlePayV2 payment call decoded from the calldata.
+///
+/// ## Usage
+/// - Audit the payments of a tx e.g. of [`crate::types::OcPayReceipt::tx_hash`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BundlePayCall {
+	BatchPaymentMSMT { relayer_index: u16, payments: Vec<Payment>, payments_nc: Vec<PermitPayment> },
+	BatchPaymentSSMT { relayer_index: u16, payments: Vec<Payment> },
+	BatchPaymentSSST { relayer_index: u16, payments: Vec<Payment> },
+	BatchPaymentSSPermit { relayer_index: u16, pay: PermitPayment },
+	SinglePaymentSSSTSR { relayer_index: u16, payment: Payment },
+}
+
+impl BundlePayCall {
+	pub fn relayer_index(&self) -> u16 {
+		match self {
+			Self::BatchPaymentMSMT { relayer_index, .. } |
+			Self::BatchPaymentSSMT { relayer_index, .. } |
+			Self::BatchPaymentSSST { relayer_index, .. } |
+			Self::BatchPaymentSSPermit { relayer_index, .. } |
+			Self::SinglePaymentSSSTSR { relayer_index, .. } => *relayer_index,
+		}
+	}
+
+	/// All the transfers of the call (incl. the NC ones).
+	pub fn payments(&self) -> Vec<&Payment> {
+		match self {
+			Self::BatchPaymentMSMT { payments, payments_nc, .. } => payments
+				.iter()
+				.chain(payments_nc.iter().flat_map(|pay| &pay.payments))
+				.collect(),
+			Self::BatchPaymentSSMT { payments, .. } | Self::BatchPaymentSSST { payments, .. } =>
+				payments.iter().collect(),
+			Self::BatchPaymentSSPermit { pay, .. } => pay.payments.iter().collect(),
+			Self::SinglePaymentSSSTSR { payment, .. } => vec![payment],
+		}
+	}
+}
+
+/// Custom errors of BundlePayV2, decoded from the revert data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundlePayRevert {
+	InvalidRelayer,
+	ZeroAddress,
+}
+
+/// Decode the BundlePayV2 payment call with the tokens mapped back to the stablecoins on the
+/// chain.
+///
+/// NOTE: Errs for the non-payment calls (e.g. `setRelayer`) & the calls with arrays of different
+/// lengths (which revert onchain).
+pub fn decode_bundle_pay_calldata(
+	calldata: &[u8],
+	chain: ChainName,
+) -> Result<BundlePayCall, UfiError> {
+	let payment = |from, to, token, amount| Payment {
+		from,
+		to,
+		token,
+		coin: StableCoin::from_address_on(token, chain),
+		amount,
+	};
+
+	let call = BundlePayV2Calls::abi_decode(calldata)
+		.map_err(|e| UfiError::InvalidCalldata(e.to_string()))?;
+	let call = match call {
+		BundlePayV2Calls::batchPaymentMSMT(call) => {
+			ensure_same_len(call.froms.len(), call.tos.len())?;
+			ensure_same_len(call.froms.len(), call.tokens.len())?;
+			ensure_same_len(call.froms.len(), call.amounts.len())?;
+			let payments = (0..call.froms.len())
+				.map(|i| payment(call.froms[i], call.tos[i], call.tokens[i], call.amounts[i]))
+				.collect();
+			let payments_nc = call
+				.paymentsNC
+				.into_iter()
+				.map(|pay| decode_permit_payment(pay, chain))
+				.collect::<Result<_, _>>()?;
+			BundlePayCall::BatchPaymentMSMT {
+				relayer_index: call.relayerIndex,
+				payments,
+				payments_nc,
+			}
+		},
+		BundlePayV2Calls::batchPaymentSSMT(call) => {
+			ensure_same_len(call.tokens.len(), call.tos.len())?;
+			ensure_same_len(call.tokens.len(), call.amounts.len())?;
+			let payments = (0..call.tokens.len())
+				.map(|i| payment(call.from, call.tos[i], call.tokens[i], call.amounts[i]))
+				.collect();
+			BundlePayCall::BatchPaymentSSMT { relayer_index: call.relayerIndex, payments }
+		},
+		BundlePayV2Calls::batchPaymentSSST(call) => {
+			ensure_same_len(call.tos.len(), call.amounts.len())?;
+			let payments = (0..call.tos.len())
+				.map(|i| payment(call.from, call.tos[i], call.token, call.amounts[i]))
+				.collect();
+			BundlePayCall::BatchPaymentSSST { relayer_index: call.relayerIndex, payments }
+		},
+		BundlePayV2Calls::batchPaymentSSPermit(call) => BundlePayCall::BatchPaymentSSPermit {
+			relayer_index: call.relayerIndex,
+			pay: decode_permit_payment(call.pay, chain)?,
+		},
+		BundlePayV2Calls::singlePaymentSSSTSR(call) => BundlePayCall::SinglePaymentSSSTSR {
+			relayer_index: call.relayerIndex,
+			payment: payment(call.from, call.to, call.token, call.amount),
+		},
+		BundlePayV2Calls::PERMIT2(_) |
+		BundlePayV2Calls::relayers(_) |
+		BundlePayV2Calls::setRelayer(_) |
+		BundlePayV2Calls::setRelayers(_) =>
+			return Err(UfiError::InvalidCalldata("Not a payment call".to_owned())),
+	};
+	Ok(call)
+}
+
+fn decode_permit_payment(
+	pay: BundlePayV2::BatchPaymentSSPermit,
+	chain: ChainName,
+) -> Result<PermitPayment, UfiError> {
+	let BundlePayV2::BatchPaymentSSPermit { from, permit, transferDetails, signature } = pay;
+	ensure_same_len(permit.permitted.len(), transferDetails.len())?;
+
+	let payments = permit
+		.permitted
+		.iter()
+		.zip(transferDetails)
+		.map(|(permitted, details)| Payment {
+			from,
+			to: details.to,
+			token: permitted.token,
+			coin: StableCoin::from_address_on(permitted.token, chain),
+			amount: details.requestedAmount,
+		})
+		.collect();
+	Ok(PermitPayment { from, nonce: permit.nonce, deadline: permit.deadline, signature, payments })
+}
+
+/// Decode the custom error of BundlePayV2 from the revert data.
+///
+/// ## Returns
+/// `None` if the revert isn't of a BundlePayV2 custom error e.g. bubbled up from Permit2 or the
+/// token.
+pub fn decode_bundle_pay_revert(data: &[u8]) -> Option<BundlePayRevert> {
+	match BundlePayV2Errors::abi_decode(data).ok()? {
+		BundlePayV2Errors::InvalidRelayer(_) => Some(BundlePayRevert::InvalidRelayer),
+		BundlePayV2Errors::ZeroAddress(_) => Some(BundlePayRevert::ZeroAddress),
+	}
+}
+
+/// As BundlePayV2 reverts with `ZeroAddress`.
+fn ensure_non_zero(addresses: &[Address]) -> Result<(), UfiError> {
+	if addresses.iter().any(|address| address.is_zero()) {
+		return Err(UfiError::ZeroAddress);
+	}
+	Ok(())
+}
+
+fn ensure_same_len(len: usize, other: usize) -> Result<(), UfiError> {
+	if len != other {
+		return Err(UfiError::LengthMismatch(len, other));
+	}
+	Ok(())
+}
+
+/// Test
+/// ```sh
+/// RUSTFLAGS="-Awarnings" cargo t -p unifi-sdk-primitives -F utils -- evm::tests --show-output
+/// ```
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_utils;
+	use alloy_primitives::hex;
+
+	const FROM: Address = Address::repeat_byte(0x11);
+	const TO: Address = Address::repeat_byte(0x22);
+	const TOKEN: Address = Address::repeat_byte(0x33);
+
+	fn decode<C: SolCall>(calldata: &str) -> C {
+		C::abi_decode(&hex::decode(calldata).unwrap()).unwrap()
+	}
+
+	fn permit() -> PermitBatchTransferFrom {
+		test_utils::permit(&[(TOKEN, 100)], 7, 1_700_000_000)
+	}
+
+	fn nc_payment() -> BundlePayV2::BatchPaymentSSPermit {
+		permit_payment(FROM, permit(), &[(TO, U256::from(100))], &[0x55; 65]).unwrap()
+	}
+
+	#[test]
+	fn builds_calldata() {
+		let amounts = [U256::from(1), U256::from(2)];
+
+		let calldata = calldata_single_payment_ssstsr(1, FROM, TO, TOKEN, amounts[0]).unwrap();
+		assert!(
+			calldata.starts_with(&get_data_hex(
+				BundlePayV2::singlePaymentSSSTSRCall::SELECTOR.to_vec()
+			))
+		);
+		let call = decode::<BundlePayV2::singlePaymentSSSTSRCall>(&calldata);
+		assert_eq!((call.relayerIndex, call.from, call.to, call.token), (1, FROM, TO, TOKEN));
+
+		let calldata = calldata_batch_payment_ssst(2, FROM, TOKEN, &[TO, FROM], &amounts).unwrap();
+		let call = decode::<BundlePayV2::batchPaymentSSSTCall>(&calldata);
+		assert_eq!(
+			(call.relayerIndex, call.tos, call.amounts),
+			(2, vec![TO, FROM], amounts.to_vec())
+		);
+
+		let calldata =
+			calldata_batch_payment_ssmt(3, FROM, &[TOKEN, TO], &[TO, TO], &amounts).unwrap();
+		let call = decode::<BundlePayV2::batchPaymentSSMTCall>(&calldata);
+		assert_eq!((call.from, call.tokens), (FROM, vec![TOKEN, TO]));
+
+		let calldata = calldata_batch_payment_ss_permit(4, nc_payment()).unwrap();
+		let call = decode::<BundlePayV2::batchPaymentSSPermitCall>(&calldata);
+		assert_eq!(call.pay.permit.nonce, U256::from(7));
+		assert_eq!(call.pay.transferDetails[0].to, TO);
+		assert_eq!(call.pay.signature.as_ref(), [0x55; 65]);
+
+		let calldata = calldata_batch_payment_msmt(
+			5,
+			&[FROM],
+			&[TO],
+			&[TOKEN],
+			&amounts[..1],
+			vec![nc_payment()],
+		)
+		.unwrap();
+		let call = decode::<BundlePayV2::batchPaymentMSMTCall>(&calldata);
+		assert_eq!((call.froms, call.paymentsNC.len()), (vec![FROM], 1));
+		// NC only
+		assert!(calldata_batch_payment_msmt(5, &[], &[], &[], &[], vec![nc_payment()]).is_ok());
+	}
+
+	#[test]
+	fn validates_inputs() {
+		let amounts = [U256::from(1), U256::from(2)];
+		let zero = Address::ZERO;
+
+		assert_eq!(
+			calldata_single_payment_ssstsr(1, FROM, zero, TOKEN, amounts[0]),
+			Err(UfiError::ZeroAddress)
+		);
+		assert_eq!(
+			calldata_batch_payment_ssst(1, FROM, TOKEN, &[TO], &amounts),
+			Err(UfiError::LengthMismatch(1, 2))
+		);
+		assert_eq!(
+			calldata_batch_payment_ssst(1, FROM, TOKEN, &[], &[]),
+			Err(UfiError::EmptyBatch)
+		);
+		assert_eq!(
+			calldata_batch_payment_ssst(1, FROM, TOKEN, &[TO, zero], &amounts),
+			Err(UfiError::ZeroAddress)
+		);
+		assert_eq!(
+			calldata_batch_payment_ssmt(1, FROM, &[TOKEN, TOKEN], &[TO], &amounts),
+			Err(UfiError::LengthMismatch(2, 1))
+		);
+		assert_eq!(
+			calldata_batch_payment_ssmt(1, zero, &[TOKEN], &[TO], &amounts[..1]),
+			Err(UfiError::ZeroAddress)
+		);
+		assert_eq!(
+			calldata_batch_payment_msmt(1, &[FROM], &[TO], &[TOKEN, TOKEN], &amounts[..1], vec![]),
+			Err(UfiError::LengthMismatch(1, 2))
+		);
+		assert_eq!(
+			calldata_batch_payment_msmt(1, &[], &[], &[], &[], vec![]),
+			Err(UfiError::EmptyBatch)
+		);
+
+		let mut pay = nc_payment();
+		pay.transferDetails.clear();
+		assert_eq!(calldata_batch_payment_ss_permit(1, pay), Err(UfiError::LengthMismatch(1, 0)));
+
+		// NC payments not built via `permit_payment`
+		let mut pay = nc_payment();
+		pay.transferDetails[0].to = zero;
+		assert_eq!(calldata_batch_payment_ss_permit(1, pay.clone()), Err(UfiError::ZeroAddress));
+		assert_eq!(
+			calldata_batch_payment_msmt(1, &[FROM], &[TO], &[TOKEN], &amounts[..1], vec![pay]),
+			Err(UfiError::ZeroAddress)
+		);
+		let mut pay = nc_payment();
+		pay.transferDetails[0].requestedAmount = U256::from(101);
+		assert_eq!(
+			calldata_batch_payment_msmt(1, &[], &[], &[], &[], vec![nc_payment(), pay]),
+			Err(UfiError::PermitAmountExceeded(U256::from(101), U256::from(100)))
+		);
+		let mut pay = nc_payment();
+		pay.signature = Bytes::from_static(&[0x55; 10]);
+		assert_eq!(
+			calldata_batch_payment_ss_permit(1, pay),
+			Err(UfiError::InvalidSignatureLength(10))
+		);
+
+		let permit = permit();
+		let sig = [0x55; 65];
+		assert_eq!(
+			permit_payment(FROM, permit.clone(), &[(TO, U256::from(101))], &sig),
+			Err(UfiError::PermitAmountExceeded(U256::from(101), U256::from(100)))
+		);
+		assert_eq!(
+			permit_payment(FROM, permit.clone(), &[(zero, U256::from(1))], &sig),
+			Err(UfiError::ZeroAddress)
+		);
+		assert_eq!(
+			permit_payment(FROM, permit.clone(), &[(TO, U256::from(1))], &sig[..63]),
+			Err(UfiError::InvalidSignatureLength(63))
+		);
+		assert_eq!(permit_payment(FROM, permit, &[], &sig), Err(UfiError::LengthMismatch(1, 0)));
+		assert_eq!(
+			permit_payment(FROM, PermitBatchTransferFrom::default(), &[], &sig),
+			Err(UfiError::EmptyPermit)
+		);
+	}
+
+	#[test]
+	fn decodes_calldata() {
+		let chain = ChainName::Ethereum;
+		let usdt = StableCoin::USDT.address_on(chain).unwrap();
+		let dai = StableCoin::DAI.address_on(chain).unwrap();
+		let amounts = [U256::from(1_500_000), U256::from(2) * U256::from(10).pow(U256::from(18))];
+		let decode = |calldata: String| {
+			decode_bundle_pay_calldata(&hex::decode(calldata).unwrap(), chain).unwrap()
+		};
+
+		let call = decode(calldata_single_payment_ssstsr(1, FROM, TO, usdt, amounts[0]).unwrap());
+		let payment = Payment {
+			from: FROM,
+			to: TO,
+			token: usdt,
+			coin: Some(StableCoin::USDT),
+			amount: amounts[0],
+		};
+		assert_eq!(
+			call,
+			BundlePayCall::SinglePaymentSSSTSR { relayer_index: 1, payment: payment.clone() }
+		);
+		assert_eq!(payment.coin_amount(chain).unwrap().to_string(), "1.500000");
+
+		let call = decode(
+			calldata_batch_payment_ssmt(2, FROM, &[usdt, dai], &[TO, TO], &amounts).unwrap(),
+		);
+		assert_eq!(call.relayer_index(), 2);
+		let coins = call.payments().iter().map(|p| (p.coin, p.amount)).collect::<Vec<_>>();
+		assert_eq!(
+			coins,
+			[(Some(StableCoin::USDT), amounts[0]), (Some(StableCoin::DAI), amounts[1])]
+		);
+		assert_eq!(
+			call.payments()[1].coin_amount(chain).unwrap().to_string(),
+			"2.000000000000000000"
+		);
+
+		// unknown token
+		let call =
+			decode(calldata_batch_payment_ssst(3, FROM, TOKEN, &[TO, FROM], &amounts).unwrap());
+		assert!(
+			matches!(&call, BundlePayCall::BatchPaymentSSST { payments, .. } if payments.len() == 2)
+		);
+		assert!(call.payments().iter().all(|p| p.coin.is_none() && p.token == TOKEN));
+		assert_eq!(call.payments()[1].to, FROM);
+
+		let call = decode(
+			calldata_batch_payment_msmt(
+				4,
+				&[FROM],
+				&[TO],
+				&[usdt],
+				&amounts[..1],
+				vec![nc_payment()],
+			)
+			.unwrap(),
+		);
+		let BundlePayCall::BatchPaymentMSMT { payments_nc, .. } = &call else {
+			panic!("{:?}", call);
+		};
+		assert_eq!(payments_nc[0].nonce, U256::from(7));
+		assert_eq!(payments_nc[0].signature.as_ref(), [0x55; 65]);
+		assert_eq!(call.payments().len(), 2);
+		assert_eq!((call.payments()[1].token, call.payments()[1].amount), (TOKEN, U256::from(100)));
+
+		let call = decode(calldata_batch_payment_ss_permit(5, nc_payment()).unwrap());
+		assert!(matches!(call, BundlePayCall::BatchPaymentSSPermit { relayer_index: 5, .. }));
+
+		// not payments
+		let set_relayer =
+			BundlePayV2::setRelayerCall { callerRelayerIndex: 0, relayerIndex: 1, relayer: FROM };
+		assert!(matches!(
+			decode_bundle_pay_calldata(&set_relayer.abi_encode(), chain),
+			Err(UfiError::InvalidCalldata(_))
+		));
+		assert!(matches!(
+			decode_bundle_pay_calldata(&calldata_approve(TO, U256::MAX), chain),
+			Err(UfiError::InvalidCalldata(_))
+		));
+
+		// lengths mismatch
+		let call = BundlePayV2::batchPaymentSSSTCall {
+			relayerIndex: 1,
+			from: FROM,
+			token: usdt,
+			tos: vec![TO],
+			amounts: amounts.to_vec(),
+		};
+		assert_eq!(
+			decode_bundle_pay_calldata(&call.abi_encode(), chain),
+			Err(UfiError::LengthMismatch(1, 2))
+		);
+	}
+
+	#[test]
+	fn decodes_revert() {
+		use alloy_sol_types::SolError;
+
+		assert_eq!(
+			decode_bundle_pay_revert(&BundlePayV2::InvalidRelayer {}.abi_encode()),
+			Some(BundlePayRevert::InvalidRelayer)
+		);
+		assert_eq!(decode_bundle_pay_revert(&hex!("d92e233d")), Some(BundlePayRevert::ZeroAddress));
+		// `Error(string)`
+		assert_eq!(
+			decode_bundle_pay_revert(&alloy_sol_types::Revert::from("nope").abi_encode()),
+			None
+		);
+		assert_eq!(decode_bundle_pay_revert(&[]), None);
+	}
+}
@@ -1,15 +1,24 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 
+pub mod amount;
+pub mod decimal;
 pub mod errors;
 pub mod evm;
+#[cfg(feature = "utils")]
+pub mod nonce;
 /// TODO: put inside evm module
 pub mod permit2;
+#[cfg(feature = "utils")]
+pub mod signer;
+#[cfg(test)]
+pub(crate) mod test_utils;
+pub mod tokens;
 pub mod types;
 #[cfg(feature = "utils")]
 pub mod utils;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ApiResponse<T: Debug> {
 	pub status: String,
 	pub data: T,
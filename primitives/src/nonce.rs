@@ -0,0 +1,253 @@
+//! Permit2 (unordered) nonces of the signature transfers.
+//!
+//! Permit2 tracks the used nonces of an owner in a bitmap: the upper 248 bits of the nonce are the
+//! word position & the lowest 8 bits are the bit position in the word. A nonce can be used only
+//! once, so reusing one (e.g. in 2 concurrent batches) reverts the latter.
+
+use alloy_primitives::U256;
+use std::collections::BTreeMap;
+
+/// Split the nonce into its (word position, bit position).
+pub fn split_nonce(nonce: U256) -> (U256, u8) {
+	(nonce >> 8, nonce.byte(0))
+}
+
+/// Nonce at the bit position in the word i.e. the inverse of [`split_nonce`].
+///
+/// NOTE: Only the lowest 248 bits of `word_pos` are used.
+pub fn join_nonce(word_pos: U256, bit_pos: u8) -> U256 {
+	(word_pos << 8) | U256::from(bit_pos)
+}
+
+/// Allocates unused nonces of an owner from the snapshots of the onchain bitmap words, while
+/// tracking the nonces allocated (but not yet used onchain) locally.
+///
+/// Nonces are allocated only in the words with a snapshot, lowest first. When all of them are
+/// used up, load the snapshot of [`NonceAllocator::next_word_pos`].
+///
+/// ## Example
+/// ```
+/// use alloy_primitives::U256;
+/// use unifi_sdk_primitives::nonce::NonceAllocator;
+///
+/// let mut nonces = NonceAllocator::new();
+/// assert_eq!(nonces.allocate(), None);
+///
+/// // nonces 0 & 1 used onchain
+/// nonces.insert_bitmap(U256::ZERO, U256::from(0b11));
+/// assert_eq!(nonces.allocate(), Some(U256::from(2)));
+/// assert_eq!(nonces.allocate(), Some(U256::from(3)));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct NonceAllocator {
+	/// word position -> onchain bitmap
+	onchain: BTreeMap<U256, U256>,
+	/// word position -> bitmap of the locally allocated nonces
+	allocated: BTreeMap<U256, U256>,
+}
+
+impl NonceAllocator {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Set the snapshot of the onchain bitmap (i.e. `nonceBitmap(owner, word_pos)`) of the word,
+	/// replacing the older one. The local allocations are kept.
+	pub fn insert_bitmap(&mut self, word_pos: U256, bitmap: U256) {
+		self.onchain.insert(word_pos, bitmap);
+	}
+
+	/// Word position after the highest one with a snapshot, to be loaded next.
+	pub fn next_word_pos(&self) -> U256 {
+		self.onchain
+			.last_key_value()
+			.map_or(U256::ZERO, |(&word_pos, _)| word_pos + U256::ONE)
+	}
+
+	/// Whether the nonce is used onchain or allocated locally.
+	///
+	/// ## Returns
+	/// `None` if there's no snapshot of the nonce's word.
+	pub fn is_used(&self, nonce: U256) -> Option<bool> {
+		let (word_pos, bit_pos) = split_nonce(nonce);
+		self.used_bitmap(word_pos).map(|bitmap| bitmap.bit(bit_pos as usize))
+	}
+
+	/// Allocate the lowest unused nonce.
+	///
+	/// ## Returns
+	/// `None` if all the nonces of the words with a snapshot are used.
+	pub fn allocate(&mut self) -> Option<U256> {
+		let (word_pos, bit_pos) = self.onchain.keys().find_map(|&word_pos| {
+			let unused = !self.used_bitmap(word_pos)?;
+			(!unused.is_zero()).then(|| (word_pos, unused.trailing_zeros()))
+		})?;
+
+		let allocated = self.allocated.entry(word_pos).or_default();
+		allocated.set_bit(bit_pos, true);
+		Some(join_nonce(word_pos, bit_pos as u8))
+	}
+
+	/// Allocate `n` unused nonces e.g. for concurrent batches. All or nothing.
+	pub fn allocate_many(&mut self, n: usize) -> Option<Vec<U256>> {
+		let nonces = (0..n).map_while(|_| self.allocate()).collect::<Vec<_>>();
+		if nonces.len() < n {
+			nonces.iter().for_each(|&nonce| self.release(nonce));
+			return None;
+		}
+		Some(nonces)
+	}
+
+	/// Release the locally allocated nonce e.g. if the permit was never submitted.
+	///
+	/// NOTE: A nonce used onchain stays used.
+	pub fn release(&mut self, nonce: U256) {
+		let (word_pos, bit_pos) = split_nonce(nonce);
+		if let Some(allocated) = self.allocated.get_mut(&word_pos) {
+			allocated.set_bit(bit_pos as usize, false);
+		}
+	}
+
+	fn used_bitmap(&self, word_pos: U256) -> Option<U256> {
+		let onchain = self.onchain.get(&word_pos)?;
+		Some(*onchain | self.allocated.get(&word_pos).copied().unwrap_or_default())
+	}
+}
+
+#[cfg(feature = "provider")]
+mod provider {
+	use super::*;
+	use crate::{permit2::IPermit2, types::ChainName};
+	use alloy_primitives::Address;
+	use alloy_provider::Provider;
+
+	/// Fetch `nonceBitmap(owner, word_pos)` from Permit2 on the chain.
+	pub async fn fetch_nonce_bitmap<P: Provider>(
+		provider: &P,
+		chain: ChainName,
+		owner: Address,
+		word_pos: U256,
+	) -> eyre::Result<U256> {
+		let permit2 = IPermit2::new(chain.get_permit2_sc_addr(), provider);
+		Ok(permit2.nonceBitmap(owner, word_pos).call().await?)
+	}
+
+	impl NonceAllocator {
+		/// Allocate the lowest unused nonce of the owner, loading the snapshot of the next word
+		/// (from Permit2 on the chain) if all the loaded ones are used.
+		pub async fn allocate_with<P: Provider>(
+			&mut self,
+			provider: &P,
+			chain: ChainName,
+			owner: Address,
+		) -> eyre::Result<U256> {
+			loop {
+				if let Some(nonce) = self.allocate() {
+					return Ok(nonce);
+				}
+				let word_pos = self.next_word_pos();
+				let bitmap = fetch_nonce_bitmap(provider, chain, owner, word_pos).await?;
+				self.insert_bitmap(word_pos, bitmap);
+			}
+		}
+	}
+}
+#[cfg(feature = "provider")]
+pub use provider::fetch_nonce_bitmap;
+
+/// Test
+/// ```sh
+/// RUSTFLAGS="-Awarnings" cargo t -p unifi-sdk-primitives -F utils -- nonce::tests --show-output
+/// ```
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn splits_nonce() {
+		assert_eq!(split_nonce(U256::ZERO), (U256::ZERO, 0));
+		assert_eq!(split_nonce(U256::from(255)), (U256::ZERO, 255));
+		assert_eq!(split_nonce(U256::from(256)), (U256::ONE, 0));
+		assert_eq!(split_nonce(U256::from(0x12_3456)), (U256::from(0x1234), 0x56));
+		assert_eq!(split_nonce(U256::MAX), (U256::MAX >> 8, 255));
+
+		for nonce in [U256::ZERO, U256::from(511), U256::from(1_000_000), U256::MAX] {
+			let (word_pos, bit_pos) = split_nonce(nonce);
+			assert_eq!(join_nonce(word_pos, bit_pos), nonce);
+		}
+	}
+
+	#[test]
+	fn allocates_unused_nonces() {
+		let mut nonces = NonceAllocator::new();
+		assert_eq!(nonces.allocate(), None);
+		assert_eq!(nonces.next_word_pos(), U256::ZERO);
+		assert_eq!(nonces.is_used(U256::ZERO), None);
+
+		// 0, 1 & 3 used onchain
+		nonces.insert_bitmap(U256::ZERO, U256::from(0b1011));
+		assert_eq!(nonces.is_used(U256::from(1)), Some(true));
+		assert_eq!(nonces.is_used(U256::from(2)), Some(false));
+		assert_eq!(nonces.allocate(), Some(U256::from(2)));
+		assert_eq!(nonces.allocate(), Some(U256::from(4)));
+		assert_eq!(nonces.is_used(U256::from(2)), Some(true));
+
+		// released nonce is reused
+		nonces.release(U256::from(2));
+		assert_eq!(nonces.allocate(), Some(U256::from(2)));
+		// onchain nonce stays used
+		nonces.release(U256::from(3));
+		assert_eq!(nonces.is_used(U256::from(3)), Some(true));
+
+		// newer snapshot keeps the local allocations
+		nonces.insert_bitmap(U256::ZERO, U256::from(0b1111_1011));
+		assert_eq!(nonces.allocate(), Some(U256::from(8)));
+		assert_eq!(nonces.allocate_many(3).unwrap(), [9, 10, 11].map(U256::from));
+	}
+
+	#[test]
+	fn moves_to_next_word() {
+		let mut nonces = NonceAllocator::new();
+		nonces.insert_bitmap(U256::ZERO, U256::MAX ^ (U256::ONE << 255));
+		assert_eq!(nonces.allocate(), Some(U256::from(255)));
+		assert_eq!(nonces.allocate(), None);
+		assert_eq!(nonces.next_word_pos(), U256::ONE);
+
+		nonces.insert_bitmap(U256::ONE, U256::ONE);
+		assert_eq!(nonces.allocate_many(255).unwrap().first(), Some(&U256::from(257)));
+		assert_eq!(nonces.allocate_many(1), None);
+
+		// all or nothing
+		nonces.insert_bitmap(U256::from(2), U256::MAX >> 2);
+		assert_eq!(nonces.allocate_many(3), None);
+		assert_eq!(nonces.allocate_many(2).unwrap(), [3 * 256 - 2, 3 * 256 - 1].map(U256::from));
+	}
+
+	/// Needs a local Anvil node with Permit2 deployed e.g. forked from a mainnet:
+	/// ```sh
+	/// anvil --fork-url https://ethereum-rpc.publicnode.com
+	/// RUSTFLAGS="-Awarnings" cargo t -p unifi-sdk-primitives -F provider -- nonce::tests --ignored
+	/// ```
+	#[cfg(feature = "provider")]
+	#[tokio::test]
+	#[ignore = "needs a local Anvil node"]
+	async fn fetches_nonce_bitmap() {
+		use crate::types::ChainName;
+		use alloy_primitives::Address;
+		use alloy_provider::ProviderBuilder;
+
+		let url = std::env::var("ANVIL_URL").unwrap_or_else(|_| "http://127.0.0.1:8545".to_owned());
+		let provider = ProviderBuilder::new().connect_http(url.parse().unwrap());
+		let owner = Address::repeat_byte(0xab);
+
+		let bitmap = fetch_nonce_bitmap(&provider, ChainName::Anvil, owner, U256::ZERO)
+			.await
+			.unwrap();
+		assert_eq!(bitmap, U256::ZERO);
+
+		let mut nonces = NonceAllocator::new();
+		let nonce = nonces.allocate_with(&provider, ChainName::Anvil, owner).await.unwrap();
+		assert_eq!(nonce, U256::ZERO);
+		assert_eq!(nonces.next_word_pos(), U256::ONE);
+	}
+}
@@ -1,25 +1,42 @@
-use alloy_sol_types::{Eip712Domain, sol};
+use crate::{
+	amount::Amount,
+	errors::UfiError,
+	types::{ChainName, StableCoin},
+};
+#[cfg(feature = "utils")]
+use alloy_primitives::Signature;
+use alloy_primitives::{Address, B256, U256};
+use alloy_sol_types::{Eip712Domain, SolStruct, sol};
 use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 // Codegen from artifact.
 sol!(
 	#[allow(missing_docs)]
-	#[sol(rpc)]
+	#[sol(rpc, all_derives)]
 	BundlePayV2,
 	"abi/BundlePayV2.json"
 );
 
+sol!(
+	#[sol(rpc)]
+	interface IPermit2 {
+		/// Bitmap of the used (unordered) nonces of the owner in the word.
+		function nonceBitmap(address owner, uint256 wordPos) external view returns (uint256);
+	}
+);
+
 // NOTE: The permit struct that has to be signed is different from the contract input struct
 // even though they have the same name.
 // Also note that the EIP712 hash of this struct is sensitive to the order of the fields.
 sol! {
-	#[derive(Debug, Default, Serialize, Deserialize)]
+	#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 	struct TokenPermissions {
 		address token;
 		uint256 amount;
 	}
 
-	#[derive(Debug, Default, Serialize, Deserialize)]
+	#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 	struct PermitBatchTransferFrom {
 		TokenPermissions[] permitted;
 		address spender;
@@ -48,6 +65,81 @@ pub struct DomainPermitForSig {
 	pub permit: PermitBatchTransferFrom,
 }
 
+impl DomainPermitForSig {
+	/// Permit under the Permit2 domain of the chain.
+	pub fn new(chain: ChainName, permit: PermitBatchTransferFrom) -> Self {
+		Self { domain: permit2_domain(chain), permit }
+	}
+
+	/// EIP712 hash of the permit under the domain i.e. the digest to be signed by the payer.
+	pub fn eip712_hash(&self) -> B256 {
+		self.permit.eip712_signing_hash(&self.domain)
+	}
+
+	/// Recover the signer (i.e. the payer) of the permit, as done by Permit2.
+	///
+	/// `sig` is the `signature` bytes passed to Permit2 i.e. 65 bytes `r || s || v` (`v` being
+	/// 27 or 28) or 64 bytes EIP2098 compact `r || vs`.
+	pub fn recover_signer(&self, sig: &[u8]) -> Result<Address, UfiError> {
+		let signature = match sig.len() {
+			65 => {
+				let y_parity = match sig[64] {
+					27 => false,
+					28 => true,
+					// `ecrecover` returns the zero address
+					_ => return Err(UfiError::InvalidSignature),
+				};
+				Signature::new(
+					U256::from_be_slice(&sig[..32]),
+					U256::from_be_slice(&sig[32..64]),
+					y_parity,
+				)
+			},
+			64 => Signature::from_erc2098(sig),
+			len => return Err(UfiError::InvalidSignatureLength(len)),
+		};
+
+		signature
+			.recover_address_from_prehash(&self.eip712_hash())
+			.map_err(|_| UfiError::InvalidSignature)
+	}
+
+	/// Verify that the permit is signed by `expected_owner` & hasn't expired, as done by Permit2
+	/// before the transfer.
+	///
+	/// NOTE: Only for EOAs. Smart contract wallets (EIP1271) are verified onchain.
+	pub fn verify(&self, sig: &[u8], expected_owner: Address) -> Result<(), UfiError> {
+		self.verify_at(sig, expected_owner, unix_now())
+	}
+
+	/// [`Self::verify`] at the (unix) timestamp `now` e.g. of the block.
+	pub fn verify_at(&self, sig: &[u8], expected_owner: Address, now: u64) -> Result<(), UfiError> {
+		// Permit2 allows the tx in the deadline's block.
+		if U256::from(now) > self.permit.deadline {
+			return Err(UfiError::SignatureExpired(self.permit.deadline));
+		}
+
+		let signer = self.recover_signer(sig)?;
+		if signer != expected_owner {
+			return Err(UfiError::InvalidSigner(expected_owner, signer));
+		}
+		Ok(())
+	}
+}
+
+/// EIP712 domain of the Permit2 contract on the chain.
+///
+/// NOTE: Permit2 has no `version` in its domain.
+pub fn permit2_domain(chain: ChainName) -> Eip712Domain {
+	Eip712Domain::new(
+		Some("Permit2".into()),
+		None,
+		Some(U256::from(chain.chain_id())),
+		Some(chain.get_permit2_sc_addr()),
+		None,
+	)
+}
+
 pub fn permit2_domain_permit_to_typed_data_json(dp: &DomainPermitForSig) -> Result<String, String> {
 	use serde_json::json;
 
@@ -106,3 +198,319 @@ pub fn permit2_domain_permit_to_typed_data_json(dp: &DomainPermitForSig) -> Resu
 
 	serde_json::to_string(&typed_data).map_err(|e| e.to_string())
 }
+
+/// Builder for [`DomainPermitForSig`] from the payments, to be signed by the payer.
+///
+/// The amounts of the same coin are aggregated into one [`TokenPermissions`] (in the order of the
+/// first payment of each coin). All the payments have to be on the same chain.
+///
+/// ## Example
+/// ```
+/// use alloy_primitives::{U256, address};
+/// use unifi_sdk_primitives::{amount::Amount, permit2::Permit2PermitBuilder};
+/// use unifi_sdk_primitives::types::{ChainName, StableCoin};
+///
+/// let (coin, chain) = (StableCoin::USDC, ChainName::Base);
+/// let (dp, typed_data_json) =
+///     Permit2PermitBuilder::new(address!("DA741C58b3e299A8c51Aa80DF70AB2881d17499c"))
+///         .payment(coin, chain, Amount::parse("10", coin, chain).unwrap())
+///         .payment(coin, chain, Amount::parse("2.5", coin, chain).unwrap())
+///         .nonce(U256::from(7))
+///         .build_with_typed_data_json()
+///         .unwrap();
+/// assert_eq!(dp.permit.permitted.len(), 1);
+/// assert_eq!(dp.permit.permitted[0].amount, U256::from(12_500_000));
+/// assert!(typed_data_json.contains("\"primaryType\":\"PermitBatchTransferFrom\""));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Permit2PermitBuilder {
+	spender: Address,
+	payments: Vec<(StableCoin, ChainName, Amount)>,
+	nonce: Option<U256>,
+	ttl: Duration,
+	deadline: Option<u64>,
+}
+
+impl Permit2PermitBuilder {
+	/// Default validity of the permit.
+	pub const DEFAULT_TTL: Duration = Duration::from_secs(30 * 60);
+
+	/// `spender` is the contract allowed to transfer the tokens e.g. BundlePayV2.
+	pub fn new(spender: Address) -> Self {
+		Self { spender, payments: vec![], nonce: None, ttl: Self::DEFAULT_TTL, deadline: None }
+	}
+
+	pub fn payment(mut self, coin: StableCoin, chain: ChainName, amount: Amount) -> Self {
+		self.payments.push((coin, chain, amount));
+		self
+	}
+
+	pub fn payments(
+		mut self,
+		payments: impl IntoIterator<Item = (StableCoin, ChainName, Amount)>,
+	) -> Self {
+		self.payments.extend(payments);
+		self
+	}
+
+	/// Unused Permit2 nonce of the payer. See [`crate::nonce`]. Required.
+	pub fn nonce(mut self, nonce: U256) -> Self {
+		self.nonce = Some(nonce);
+		self
+	}
+
+	/// Validity of the permit from the time of build. Default: [`Self::DEFAULT_TTL`].
+	pub fn ttl(mut self, ttl: Duration) -> Self {
+		self.ttl = ttl;
+		self
+	}
+
+	/// Unix timestamp until which the permit is valid. Overrides [`Self::ttl`].
+	pub fn deadline(mut self, deadline: u64) -> Self {
+		self.deadline = Some(deadline);
+		self
+	}
+
+	pub fn build(self) -> Result<DomainPermitForSig, UfiError> {
+		self.build_at(unix_now())
+	}
+
+	/// [`Self::build`] at the (unix) timestamp `now`, from which the TTL is counted.
+	pub fn build_at(self, now: u64) -> Result<DomainPermitForSig, UfiError> {
+		let Self { spender, payments, nonce, ttl, deadline } = self;
+		if spender.is_zero() {
+			return Err(UfiError::ZeroAddress);
+		}
+		let nonce = nonce.ok_or(UfiError::MissingPermitNonce)?;
+		let chain = payments.first().map(|&(_, chain, _)| chain).ok_or(UfiError::EmptyPermit)?;
+
+		// coin -> (token, total)
+		let mut totals: Vec<(StableCoin, Address, Amount)> = vec![];
+		for (coin, payment_chain, amount) in payments {
+			if payment_chain != chain {
+				return Err(UfiError::ChainMismatch(chain, payment_chain));
+			}
+			if amount.coin() != coin {
+				return Err(UfiError::CoinMismatch(coin, amount.coin()));
+			}
+			let token = coin.token_on(chain).ok_or(UfiError::TokenNotFound(coin, chain))?;
+			if amount.decimals() != token.decimals {
+				return Err(UfiError::DecimalsMismatch(token.decimals, amount.decimals()));
+			}
+			if amount.is_zero() {
+				return Err(UfiError::ZeroAmount);
+			}
+
+			match totals.iter_mut().find(|(c, ..)| *c == coin) {
+				Some((.., total)) => *total = total.checked_add(&amount)?,
+				None => totals.push((coin, token.address, amount)),
+			}
+		}
+
+		let deadline = deadline.unwrap_or_else(|| now.saturating_add(ttl.as_secs()));
+		let permit = PermitBatchTransferFrom {
+			permitted: totals
+				.into_iter()
+				.map(|(_, token, total)| TokenPermissions { token, amount: total.raw() })
+				.collect(),
+			spender,
+			nonce,
+			deadline: U256::from(deadline),
+		};
+		Ok(DomainPermitForSig::new(chain, permit))
+	}
+
+	/// [`Self::build`] along with the EIP712 typed data JSON (for `eth_signTypedData_v4`) of it.
+	pub fn build_with_typed_data_json(self) -> Result<(DomainPermitForSig, String), UfiError> {
+		let dp = self.build()?;
+		let typed_data_json =
+			permit2_domain_permit_to_typed_data_json(&dp).map_err(UfiError::TypedData)?;
+		Ok((dp, typed_data_json))
+	}
+}
+
+fn unix_now() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// Test
+/// ```sh
+/// RUSTFLAGS="-Awarnings" cargo t -p unifi-sdk-primitives -F utils -- permit2::tests --show-output
+/// ```
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		signer::Signer,
+		test_utils::{SPENDER, permit, signer},
+	};
+	use alloy_primitives::address;
+
+	const DEADLINE: u64 = 1_700_000_000;
+
+	/// USDC & USDT on Base.
+	fn domain_permit() -> DomainPermitForSig {
+		let permitted = [
+			(address!("833589fCD6eDb6E08f4c7C32D4f71b54bdA02913"), 1_000_000),
+			(address!("fde4C96c8593536E31F229EA8f37b2ADa2699bb2"), 2_000_000),
+		];
+		DomainPermitForSig::new(ChainName::Base, permit(&permitted, 7, DEADLINE))
+	}
+
+	#[test]
+	fn verifies_signature() {
+		let signer = signer();
+		let owner = signer.address();
+		let dp = domain_permit();
+		let sig = signer.sign_permit(&dp).unwrap().as_bytes();
+
+		assert_eq!(dp.recover_signer(&sig), Ok(owner));
+		assert_eq!(dp.verify_at(&sig, owner, DEADLINE - 60), Ok(()));
+		assert_eq!(dp.verify_at(&sig, owner, DEADLINE), Ok(()));
+		assert_eq!(
+			dp.verify_at(&sig, owner, DEADLINE + 1),
+			Err(UfiError::SignatureExpired(U256::from(DEADLINE)))
+		);
+		assert_eq!(dp.verify(&sig, owner), Err(UfiError::SignatureExpired(U256::from(DEADLINE))));
+
+		let other = Address::repeat_byte(0x11);
+		assert_eq!(dp.verify_at(&sig, other, DEADLINE), Err(UfiError::InvalidSigner(other, owner)));
+
+		// EIP2098 compact
+		let compact = signer.sign_permit(&dp).unwrap().as_erc2098();
+		assert_eq!(dp.recover_signer(&compact), Ok(owner));
+	}
+
+	#[test]
+	fn rejects_tampered_permits() {
+		let signer = signer();
+		let owner = signer.address();
+		let dp = domain_permit();
+		let sig = signer.sign_permit(&dp).unwrap().as_bytes();
+
+		let mut tampered = dp.clone();
+		tampered.permit.permitted[1].amount += U256::from(1);
+		assert!(matches!(
+			tampered.verify_at(&sig, owner, DEADLINE),
+			Err(UfiError::InvalidSigner(..))
+		));
+
+		// Same permit on another chain
+		let other_chain = DomainPermitForSig::new(ChainName::Optimism, dp.permit.clone());
+		assert!(matches!(
+			other_chain.verify_at(&sig, owner, DEADLINE),
+			Err(UfiError::InvalidSigner(..))
+		));
+
+		let mut swapped = dp.clone();
+		swapped.permit.permitted.reverse();
+		assert!(matches!(
+			swapped.verify_at(&sig, owner, DEADLINE),
+			Err(UfiError::InvalidSigner(..))
+		));
+
+		assert_eq!(dp.recover_signer(&sig[..63]), Err(UfiError::InvalidSignatureLength(63)));
+		let mut bad_v = sig;
+		bad_v[64] = 1;
+		assert_eq!(dp.recover_signer(&bad_v), Err(UfiError::InvalidSignature));
+		assert_eq!(dp.recover_signer(&[0; 65]), Err(UfiError::InvalidSignature));
+	}
+
+	#[test]
+	fn builds_permit() {
+		let chain = ChainName::Base;
+		let amount = |v, coin| Amount::parse(v, coin, chain).unwrap();
+		let builder = || Permit2PermitBuilder::new(SPENDER).nonce(U256::from(7));
+
+		let dp = builder()
+			.payments([
+				(StableCoin::USDC, chain, amount("0.6", StableCoin::USDC)),
+				(StableCoin::USDT, chain, amount("2", StableCoin::USDT)),
+				(StableCoin::USDC, chain, amount("0.4", StableCoin::USDC)),
+			])
+			.ttl(Duration::from_secs(60))
+			.build_at(DEADLINE - 60)
+			.unwrap();
+		assert_eq!(dp.domain, permit2_domain(chain));
+		assert_eq!(dp.permit.permitted, domain_permit().permit.permitted);
+		assert_eq!(dp.eip712_hash(), domain_permit().eip712_hash());
+
+		let dp = builder()
+			.payment(StableCoin::DAI, chain, amount("1", StableCoin::DAI))
+			.deadline(DEADLINE)
+			.build()
+			.unwrap();
+		assert_eq!(dp.permit.deadline, U256::from(DEADLINE));
+
+		let (dp, json) = builder()
+			.payment(StableCoin::USDT, chain, amount("2", StableCoin::USDT))
+			.build_with_typed_data_json()
+			.unwrap();
+		let deadline = dp.permit.deadline.to::<u64>();
+		assert!(deadline.abs_diff(unix_now() + 30 * 60) <= 5);
+		let json = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+		assert_eq!(json["domain"]["chainId"], "8453");
+		assert_eq!(json["message"]["permitted"][0]["amount"], "2000000");
+		assert_eq!(json["message"]["deadline"], deadline.to_string());
+	}
+
+	#[test]
+	fn rejects_invalid_payments() {
+		let usdc = |chain| Amount::parse("1", StableCoin::USDC, chain).unwrap();
+		let builder = || Permit2PermitBuilder::new(SPENDER).nonce(U256::ONE);
+
+		assert_eq!(builder().build().unwrap_err(), UfiError::EmptyPermit);
+		assert_eq!(
+			Permit2PermitBuilder::new(SPENDER)
+				.payment(StableCoin::USDC, ChainName::Base, usdc(ChainName::Base))
+				.build()
+				.unwrap_err(),
+			UfiError::MissingPermitNonce
+		);
+		assert_eq!(
+			Permit2PermitBuilder::new(Address::ZERO).nonce(U256::ONE).build().unwrap_err(),
+			UfiError::ZeroAddress
+		);
+		assert_eq!(
+			builder()
+				.payment(StableCoin::USDC, ChainName::Base, usdc(ChainName::Base))
+				.payment(StableCoin::USDC, ChainName::Optimism, usdc(ChainName::Optimism))
+				.build()
+				.unwrap_err(),
+			UfiError::ChainMismatch(ChainName::Base, ChainName::Optimism)
+		);
+		assert_eq!(
+			builder()
+				.payment(StableCoin::USDT, ChainName::Base, usdc(ChainName::Base))
+				.build()
+				.unwrap_err(),
+			UfiError::CoinMismatch(StableCoin::USDT, StableCoin::USDC)
+		);
+		assert_eq!(
+			builder()
+				.payment(StableCoin::USDC, ChainName::OpBNB, usdc(ChainName::Base))
+				.build()
+				.unwrap_err(),
+			UfiError::TokenNotFound(StableCoin::USDC, ChainName::OpBNB)
+		);
+		// 6 decimals on Ethereum, but 18 on BNB Chain
+		assert_eq!(
+			builder()
+				.payment(StableCoin::USDC, ChainName::BnbChain, usdc(ChainName::Ethereum))
+				.build()
+				.unwrap_err(),
+			UfiError::DecimalsMismatch(18, 6)
+		);
+		assert_eq!(
+			builder()
+				.payment(
+					StableCoin::USDC,
+					ChainName::Base,
+					Amount::zero(StableCoin::USDC, ChainName::Base).unwrap()
+				)
+				.build()
+				.unwrap_err(),
+			UfiError::ZeroAmount
+		);
+	}
+}
@@ -0,0 +1,187 @@
+//! Signing of the Permit2 batch transfers on the Rust side e.g. for the server-managed wallets.
+//!
+//! [`Signer`] is pluggable (e.g. KMS/HSM backed), while [`PrivateKeySigner`] signs locally with a
+//! secp256k1 private key.
+
+use crate::{errors::UfiError, permit2::DomainPermitForSig};
+use alloy_primitives::{Address, B256, Signature};
+use k256::ecdsa::SigningKey;
+use std::{fmt::Debug, str::FromStr};
+
+/// Signs (EIP712) digests on behalf of an EOA.
+pub trait Signer {
+	/// Address of the signing EOA.
+	fn address(&self) -> Address;
+
+	/// Sign the 32 bytes digest as is i.e. without any (EIP191) prefix.
+	fn sign_hash(&self, hash: &B256) -> Result<Signature, UfiError>;
+
+	/// Sign the EIP712 hash of the `PermitBatchTransferFrom` under the (Permit2) domain.
+	///
+	/// ## Returns
+	/// The signature whose 65 bytes (`r || s || v`) i.e. [`Signature::as_bytes`] are to be passed
+	/// to Permit2.
+	fn sign_permit(&self, dp: &DomainPermitForSig) -> Result<Signature, UfiError> {
+		self.sign_hash(&dp.eip712_hash())
+	}
+}
+
+/// Local signer from a secp256k1 private key.
+///
+/// ## Example
+/// ```
+/// use unifi_sdk_primitives::signer::{PrivateKeySigner, Signer};
+///
+/// let signer: PrivateKeySigner =
+///     "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80".parse().unwrap();
+/// assert_eq!(
+///     signer.address().to_string(),
+///     "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"
+/// );
+/// ```
+#[derive(Clone)]
+pub struct PrivateKeySigner {
+	key: SigningKey,
+	address: Address,
+}
+
+impl PrivateKeySigner {
+	pub fn from_bytes(key: &B256) -> Result<Self, UfiError> {
+		let key = SigningKey::from_bytes(&key.0.into()).map_err(|_| UfiError::InvalidPrivateKey)?;
+		let address = Address::from_private_key(&key);
+		Ok(Self { key, address })
+	}
+}
+
+/// Hex private key, with or without `0x`.
+impl FromStr for PrivateKeySigner {
+	type Err = UfiError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let key = B256::from_str(s.trim()).map_err(|_| UfiError::InvalidPrivateKey)?;
+		Self::from_bytes(&key)
+	}
+}
+
+/// NOTE: Never prints the private key.
+impl Debug for PrivateKeySigner {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("PrivateKeySigner").field("address", &self.address).finish()
+	}
+}
+
+impl Signer for PrivateKeySigner {
+	fn address(&self) -> Address {
+		self.address
+	}
+
+	/// Deterministic (RFC6979) with low-s.
+	fn sign_hash(&self, hash: &B256) -> Result<Signature, UfiError> {
+		self.key
+			.sign_prehash_recoverable(hash.as_slice())
+			.map(Signature::from)
+			.map_err(|e| UfiError::SigningFailed(e.to_string()))
+	}
+}
+
+/// Test
+/// ```sh
+/// RUSTFLAGS="-Awarnings" cargo t -p unifi-sdk-primitives -F utils -- signer::tests --show-output
+/// ```
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		permit2::{PermitBatchTransferFrom, TokenPermissions, permit2_domain},
+		test_utils::{KEY, permit, signer},
+		types::ChainName,
+	};
+	use alloy_primitives::{address, b256, hex};
+	use alloy_sol_types::SolStruct;
+
+	#[test]
+	fn parses_private_key() {
+		let signer = signer();
+		assert_eq!(signer.address(), address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266"));
+		assert_eq!(KEY[2..].parse::<PrivateKeySigner>().unwrap().address(), signer.address());
+		assert!(!format!("{:?}", signer).contains(&KEY[2..]));
+
+		for invalid in
+			["", "0x1234", &format!("0x{}", "0".repeat(64)), &format!("0x{}", "f".repeat(64))]
+		{
+			assert_eq!(
+				invalid.parse::<PrivateKeySigner>().unwrap_err(),
+				UfiError::InvalidPrivateKey
+			);
+		}
+	}
+
+	/// Constants of the Permit2 contract.
+	#[test]
+	fn matches_permit2_hashes() {
+		assert_eq!(
+			PermitBatchTransferFrom::default().eip712_type_hash(),
+			b256!("fcf35f5ac6a2c28868dc44c302166470266239195f02b0ee408334829333b766")
+		);
+		assert_eq!(
+			TokenPermissions::default().eip712_type_hash(),
+			b256!("618358ac3db8dc274f0cd8829da7e234bd48cd73c4a740aede1adec9846d06a1")
+		);
+		// `DOMAIN_SEPARATOR()` on Ethereum
+		assert_eq!(
+			permit2_domain(ChainName::Ethereum).separator(),
+			b256!("866a5aba21966af95d6c7ab78eb2b2fc913915c28be3b9aa07cc04ff903e3f28")
+		);
+	}
+
+	/// Reference vectors computed independently (EIP712 encoding & RFC6979 signing from scratch).
+	#[test]
+	fn signs_permits() {
+		let cases = [
+			(
+				DomainPermitForSig::new(
+					ChainName::Ethereum,
+					permit(
+						&[(address!("dAC17F958D2ee523a2206206994597C13D831ec7"), 10_500_000)],
+						0,
+						1_700_000_000,
+					),
+				),
+				b256!("586d6be08e4d2eaf8f4aed39113bc07f50a22b73ccf66b544382398e6fcd8401"),
+				hex!(
+					"de4d800a0f7a4483e7dbc03b98e50c9115084430531df7d2bcebdde749eeb29213c9852988a0d665afc823c2a069ec0bf5109267e2da8e900c6354add5c96c721c"
+				),
+			),
+			(
+				DomainPermitForSig::new(
+					ChainName::Polygon,
+					permit(
+						&[
+							(address!("c2132D05D31c914a87C6611C10748AEb04B58e8F"), 1_000_000),
+							(address!("3c499c542cEF5E3811e1192ce70d8cC03d5c3359"), 2_500_000),
+							(
+								address!("8f3Cf7ad23Cd3CaDbD9735AFf958023239c6A063"),
+								3 * 10u128.pow(18),
+							),
+						],
+						(1 << 8) | 5,
+						1_800_000_000,
+					),
+				),
+				b256!("898b47fa6fe4b5b46dd910c3f2385f8fc6d47bb87066a73464d05fdbea595826"),
+				hex!(
+					"b6ee28441f3aa3d136657435ee50aa75f8f1d3f8a5ebea12ee2b32279d67809c47fa35c2574c411840d2f7d3eebbb65e85e0e49b14f9dd24d8a25e078cd3da481c"
+				),
+			),
+		];
+
+		let signer = signer();
+		for (dp, hash, sig) in cases {
+			assert_eq!(dp.eip712_hash(), hash);
+
+			let signature = signer.sign_permit(&dp).unwrap();
+			assert_eq!(signature.as_bytes(), sig);
+			assert_eq!(signature.recover_address_from_prehash(&hash).unwrap(), signer.address());
+		}
+	}
+}
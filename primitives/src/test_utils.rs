@@ -0,0 +1,32 @@
+//! Fixtures shared by the unit tests.
+
+use crate::permit2::{PermitBatchTransferFrom, TokenPermissions};
+use alloy_primitives::{Address, U256, address};
+
+/// Private key of Anvil's 1st account i.e. `0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266`.
+pub(crate) const KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+/// Spender of the permits e.g. BundlePayV2.
+pub(crate) const SPENDER: Address = address!("DA741C58b3e299A8c51Aa80DF70AB2881d17499c");
+
+/// Signer of [`KEY`].
+#[cfg(feature = "utils")]
+pub(crate) fn signer() -> crate::signer::PrivateKeySigner {
+	KEY.parse().unwrap()
+}
+
+/// Permit of the (token, amount)s to [`SPENDER`].
+pub(crate) fn permit(
+	permitted: &[(Address, u128)],
+	nonce: u64,
+	deadline: u64,
+) -> PermitBatchTransferFrom {
+	PermitBatchTransferFrom {
+		permitted: permitted
+			.iter()
+			.map(|&(token, amount)| TokenPermissions { token, amount: U256::from(amount) })
+			.collect(),
+		spender: SPENDER,
+		nonce: U256::from(nonce),
+		deadline: U256::from(deadline),
+	}
+}
@@ -0,0 +1,277 @@
+//! Registry of the stablecoin (ERC20) contracts on each chain.
+//!
+//! Built-in for the mainnets (& the canonical testnet tokens). For Anvil & the testnets, custom
+//! tokens (e.g. self-deployed mocks) can be registered at runtime via [`register_token`], which
+//! take precedence over the built-in ones.
+
+use crate::{
+	errors::UfiError,
+	types::{ChainName, Mode, StableCoin},
+};
+use alloy_primitives::{Address, address};
+use std::{
+	collections::HashMap,
+	sync::{LazyLock, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+/// ERC20 contract of a stablecoin on a chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Token {
+	pub address: Address,
+	pub decimals: u8,
+}
+
+type CustomTokens = HashMap<(StableCoin, ChainName), Token>;
+
+/// (coin, chain) -> token, registered at runtime.
+static CUSTOM_TOKENS: LazyLock<RwLock<CustomTokens>> = LazyLock::new(Default::default);
+
+/// Register a custom token, replacing the existing one (if any) for the coin on the chain.
+///
+/// NOTE: Only for Anvil & the testnets, so that the mainnet addresses can't be tampered with. The
+/// address can't be the token of another coin on the chain, so that the reverse lookup (i.e.
+/// [`StableCoin::from_address_on`]) stays unambiguous.
+///
+/// ## Example
+/// ```
+/// use alloy_primitives::address;
+/// use unifi_sdk_primitives::tokens::{Token, register_token};
+/// use unifi_sdk_primitives::types::{ChainName, StableCoin};
+///
+/// let token = Token { address: address!("5FbDB2315678afecb367f032d93F642f64180aa3"), decimals: 6 };
+/// register_token(StableCoin::USDT, ChainName::Anvil, token).unwrap();
+/// assert_eq!(StableCoin::USDT.address_on(ChainName::Anvil), Some(token.address));
+/// ```
+pub fn register_token(coin: StableCoin, chain: ChainName, token: Token) -> Result<(), UfiError> {
+	if !chain.is_testnet() {
+		return Err(UfiError::TokenRegistrationNotAllowed(chain));
+	}
+	if token.address.is_zero() {
+		return Err(UfiError::ZeroAddress);
+	}
+
+	// NOTE: Checked under the write lock, so that concurrent registrations can't both pass.
+	let mut custom_tokens = custom_tokens_mut();
+	let taken_by = StableCoin::all().iter().copied().find(|&other| {
+		let other_token = custom_tokens
+			.get(&(other, chain))
+			.copied()
+			.or_else(|| other.builtin_token_on(chain));
+		other != coin && other_token.is_some_and(|other_token| other_token.address == token.address)
+	});
+	if let Some(other) = taken_by {
+		return Err(UfiError::TokenAddressTaken(token.address, other, chain));
+	}
+
+	custom_tokens.insert((coin, chain), token);
+	Ok(())
+}
+
+/// Remove the custom token (if registered) for the coin on the chain.
+///
+/// ## Returns
+/// The removed token.
+pub fn deregister_token(coin: StableCoin, chain: ChainName) -> Option<Token> {
+	custom_tokens_mut().remove(&(coin, chain))
+}
+
+/// All the (coin, chain) pairs of the token `address` (in [`ChainName::all`] order).
+///
+/// NOTE: The same address may be used on multiple chains e.g. DAI on Arbitrum & Optimism. Use
+/// [`StableCoin::from_address_on`] if the chain is known.
+pub fn lookup_address(address: Address) -> Vec<(StableCoin, ChainName)> {
+	ChainName::all(Mode::Test)
+		.iter()
+		.flat_map(|&chain| StableCoin::all().iter().map(move |&coin| (coin, chain)))
+		.filter(|&(coin, chain)| coin.address_on(chain) == Some(address))
+		.collect()
+}
+
+fn custom_tokens() -> RwLockReadGuard<'static, CustomTokens> {
+	// A panic while holding the lock can't leave the map inconsistent.
+	CUSTOM_TOKENS.read().unwrap_or_else(|e| e.into_inner())
+}
+
+fn custom_tokens_mut() -> RwLockWriteGuard<'static, CustomTokens> {
+	CUSTOM_TOKENS.write().unwrap_or_else(|e| e.into_inner())
+}
+
+impl StableCoin {
+	/// Token contract of the coin on the chain, if deployed (or registered).
+	pub fn token_on(&self, chain: ChainName) -> Option<Token> {
+		if let Some(token) = custom_tokens().get(&(*self, chain)) {
+			return Some(*token);
+		}
+		self.builtin_token_on(chain)
+	}
+
+	fn builtin_token_on(&self, chain: ChainName) -> Option<Token> {
+		let token = |address, decimals| Some(Token { address, decimals });
+		use ChainName as C;
+		use StableCoin as S;
+		match (chain, self) {
+			(C::Ethereum, S::USDT) =>
+				token(address!("dAC17F958D2ee523a2206206994597C13D831ec7"), 6),
+			(C::Ethereum, S::USDC) =>
+				token(address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"), 6),
+			(C::Ethereum, S::DAI) =>
+				token(address!("6B175474E89094C44Da98b954EedeAC495271d0F"), 18),
+			(C::Polygon, S::USDT) => token(address!("c2132D05D31c914a87C6611C10748AEb04B58e8F"), 6),
+			(C::Polygon, S::USDC) => token(address!("3c499c542cEF5E3811e1192ce70d8cC03d5c3359"), 6),
+			(C::Polygon, S::DAI) => token(address!("8f3Cf7ad23Cd3CaDbD9735AFf958023239c6A063"), 18),
+			// NOTE: Binance-Peg tokens with 18 decimals.
+			(C::BnbChain, S::USDT) =>
+				token(address!("55d398326f99059fF775485246999027B3197955"), 18),
+			(C::BnbChain, S::USDC) =>
+				token(address!("8AC76a51cc950d9822D68b83fE1Ad97B32Cd580d"), 18),
+			(C::BnbChain, S::DAI) =>
+				token(address!("1AF3F329e8BE154074D8769D1FFa4eE058B1DBc3"), 18),
+			(C::Base, S::USDT) => token(address!("fde4C96c8593536E31F229EA8f37b2ADa2699bb2"), 6),
+			(C::Base, S::USDC) => token(address!("833589fCD6eDb6E08f4c7C32D4f71b54bdA02913"), 6),
+			(C::Base, S::DAI) => token(address!("50c5725949A6F0c72E6C4a641F24049A917DB0Cb"), 18),
+			(C::Arbitrum, S::USDT) =>
+				token(address!("Fd086bC7CD5C481DCC9C85ebE478A1C0b69FCbb9"), 6),
+			(C::Arbitrum, S::USDC) =>
+				token(address!("af88d065e77c8cC2239327C5EDb3A432268e5831"), 6),
+			(C::Arbitrum, S::DAI) =>
+				token(address!("DA10009cBd5D07dd0CeCc66161FC93D7c9000da1"), 18),
+			(C::Optimism, S::USDT) =>
+				token(address!("94b008aA00579c1307B0EF2c499aD98a8ce58e58"), 6),
+			(C::Optimism, S::USDC) =>
+				token(address!("0b2C639c533813f4Aa9D7837CAf62653d097Ff85"), 6),
+			(C::Optimism, S::DAI) =>
+				token(address!("DA10009cBd5D07dd0CeCc66161FC93D7c9000da1"), 18),
+			(C::PolygonZkEvm, S::USDT) =>
+				token(address!("1E4a5963aBFD975d8c9021ce480b42188849D41d"), 6),
+			(C::PolygonZkEvm, S::USDC) =>
+				token(address!("A8CE8aee21bC2A48a5EF670afCc9274C7bbbC035"), 6),
+			(C::PolygonZkEvm, S::DAI) =>
+				token(address!("C5015b9d9161Dca7e18e32f6f25C4aD850731Fd4"), 18),
+			(C::OpBNB, S::USDT) => token(address!("9e5AAC1Ba1a2e6aEd6b32689DFcF62A509Ca96f3"), 18),
+			(C::OpBNB, S::USDC | S::DAI) => None,
+			// Circle's testnet USDC
+			(C::Sepolia, S::USDC) => token(address!("1c7D4B196Cb0C7B01d743Fbc6116a902379C7238"), 6),
+			(C::Sepolia, S::USDT | S::DAI) => None,
+			// Deployed per local setup, so register at runtime.
+			(C::Anvil, _) => None,
+		}
+	}
+
+	/// Token contract address of the coin on the chain, if deployed (or registered).
+	pub fn address_on(&self, chain: ChainName) -> Option<Address> {
+		self.token_on(chain).map(|token| token.address)
+	}
+
+	/// Decimals of the coin on the chain, if deployed (or registered).
+	///
+	/// NOTE: Differs from [`StableCoin::decimals`] on some chains e.g. USDT on BNB Chain has 18.
+	pub fn decimals_on(&self, chain: ChainName) -> Result<u8, UfiError> {
+		self.token_on(chain)
+			.map(|token| token.decimals)
+			.ok_or(UfiError::TokenNotFound(*self, chain))
+	}
+
+	/// Reverse lookup of the coin by its token address on the chain.
+	pub fn from_address_on(address: Address, chain: ChainName) -> Option<StableCoin> {
+		StableCoin::all()
+			.iter()
+			.copied()
+			.find(|coin| coin.address_on(chain) == Some(address))
+	}
+}
+
+/// Test
+/// ```sh
+/// RUSTFLAGS="-Awarnings" cargo t -p unifi-sdk-primitives -F utils -- tokens::tests --show-output
+/// ```
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn looks_up_builtin_tokens() {
+		let usdt = address!("dAC17F958D2ee523a2206206994597C13D831ec7");
+		assert_eq!(StableCoin::USDT.address_on(ChainName::Ethereum), Some(usdt));
+		assert_eq!(StableCoin::from_address_on(usdt, ChainName::Ethereum), Some(StableCoin::USDT));
+		assert_eq!(StableCoin::from_address_on(usdt, ChainName::Polygon), None);
+		assert_eq!(lookup_address(usdt), [(StableCoin::USDT, ChainName::Ethereum)]);
+
+		let dai = address!("DA10009cBd5D07dd0CeCc66161FC93D7c9000da1");
+		assert_eq!(
+			lookup_address(dai),
+			[(StableCoin::DAI, ChainName::Arbitrum), (StableCoin::DAI, ChainName::Optimism)]
+		);
+
+		assert_eq!(StableCoin::USDT.decimals_on(ChainName::BnbChain), Ok(18));
+		assert_eq!(StableCoin::USDT.decimals_on(ChainName::Ethereum), Ok(6));
+		assert_eq!(StableCoin::DAI.address_on(ChainName::OpBNB), None);
+		assert_eq!(
+			StableCoin::DAI.decimals_on(ChainName::OpBNB),
+			Err(UfiError::TokenNotFound(StableCoin::DAI, ChainName::OpBNB))
+		);
+
+		// unique (coin, address) per chain
+		for &chain in ChainName::all(Mode::Test) {
+			for &coin in StableCoin::all() {
+				if let Some(address) = coin.address_on(chain) {
+					assert_eq!(StableCoin::from_address_on(address, chain), Some(coin));
+				}
+			}
+		}
+	}
+
+	/// NOTE: The registry is global, so only Anvil's DAI & USDC are used, which no other test
+	/// looks up.
+	#[test]
+	fn registers_custom_tokens() {
+		let (dai, usdc) = (StableCoin::DAI, StableCoin::USDC);
+		let chain = ChainName::Anvil;
+		let token =
+			Token { address: address!("5FbDB2315678afecb367f032d93F642f64180aa3"), decimals: 18 };
+
+		assert_eq!(
+			register_token(dai, ChainName::Ethereum, token),
+			Err(UfiError::TokenRegistrationNotAllowed(ChainName::Ethereum))
+		);
+		assert_eq!(
+			register_token(dai, chain, Token { address: Address::ZERO, ..token }),
+			Err(UfiError::ZeroAddress)
+		);
+
+		register_token(dai, chain, token).unwrap();
+		assert_eq!(dai.token_on(chain), Some(token));
+		assert_eq!(dai.decimals_on(chain), Ok(18));
+		assert_eq!(lookup_address(token.address), [(dai, chain)]);
+
+		// replaces the existing one
+		let custom = Token { address: Address::repeat_byte(0x11), decimals: 18 };
+		register_token(dai, chain, custom).unwrap();
+		assert_eq!(dai.token_on(chain), Some(custom));
+		assert_eq!(StableCoin::from_address_on(token.address, chain), None);
+
+		// address of another coin on the chain
+		assert_eq!(
+			register_token(usdc, chain, Token { decimals: 6, ..custom }),
+			Err(UfiError::TokenAddressTaken(custom.address, dai, chain))
+		);
+		assert_eq!(usdc.token_on(chain), None);
+		// the replaced address is free again
+		register_token(usdc, chain, token).unwrap();
+		assert_eq!(StableCoin::from_address_on(token.address, chain), Some(usdc));
+		assert_eq!(deregister_token(usdc, chain), Some(token));
+
+		assert_eq!(deregister_token(dai, chain), Some(custom));
+		assert_eq!(dai.token_on(chain), None);
+		assert_eq!(deregister_token(dai, chain), None);
+	}
+
+	/// Sepolia's built-in USDC can't be reused by another coin.
+	#[test]
+	fn rejects_builtin_token_address() {
+		let usdc = StableCoin::USDC.token_on(ChainName::Sepolia).unwrap();
+		assert_eq!(
+			register_token(StableCoin::DAI, ChainName::Sepolia, usdc),
+			Err(UfiError::TokenAddressTaken(usdc.address, StableCoin::USDC, ChainName::Sepolia))
+		);
+	}
+}
@@ -1,4 +1,5 @@
-use alloy_primitives::{Address, address};
+use crate::{amount::Amount, errors::UfiError};
+use alloy_primitives::{Address, address, hex, utils::Keccak256};
 use bson::{
 	Bson::{self, Document as BsonDocument},
 	doc,
@@ -92,15 +93,17 @@ pub enum ChainName {
 	#[default]
 	Ethereum,
 	Polygon,
-	// BnbChain,
-	// Base,
-	// Arbitrum,
-	// Optimism,
-	// PolygonZkEvm,
-	// OpBNB,
 	Sepolia,
 	/// For local testing
 	Anvil,
+	// NOTE: Appended, so that the (rkyv) archived discriminants of the above stay the same. Use
+	// `ChainName::all` for the listing order.
+	BnbChain,
+	Base,
+	Arbitrum,
+	Optimism,
+	PolygonZkEvm,
+	OpBNB,
 }
 
 impl From<ChainName> for String {
@@ -124,6 +127,12 @@ impl FromStr for ChainName {
 		match chain.to_lowercase().as_str() {
 			"ethereum" => Ok(C::Ethereum),
 			"polygon" => Ok(C::Polygon),
+			"bnbchain" => Ok(C::BnbChain),
+			"base" => Ok(C::Base),
+			"arbitrum" => Ok(C::Arbitrum),
+			"optimism" => Ok(C::Optimism),
+			"polygonzkevm" => Ok(C::PolygonZkEvm),
+			"opbnb" => Ok(C::OpBNB),
 			"sepolia" => Ok(C::Sepolia),
 			"anvil" => Ok(C::Anvil),
 			_ => Err(format!("Invalid chain name: {}", chain)),
@@ -137,6 +146,12 @@ impl AsRef<str> for ChainName {
 		match self {
 			C::Ethereum => "Ethereum",
 			C::Polygon => "Polygon",
+			C::BnbChain => "BnbChain",
+			C::Base => "Base",
+			C::Arbitrum => "Arbitrum",
+			C::Optimism => "Optimism",
+			C::PolygonZkEvm => "PolygonZkEvm",
+			C::OpBNB => "OpBNB",
 			C::Sepolia => "Sepolia",
 			C::Anvil => "Anvil",
 		}
@@ -155,17 +170,78 @@ impl ChainName {
 		use ChainName as C;
 
 		if matches!(mode, Mode::Test) {
-			&[C::Ethereum, C::Polygon, C::Sepolia, C::Anvil]
+			&[
+				C::Ethereum,
+				C::Polygon,
+				C::BnbChain,
+				C::Base,
+				C::Arbitrum,
+				C::Optimism,
+				C::PolygonZkEvm,
+				C::OpBNB,
+				C::Sepolia,
+				C::Anvil,
+			]
 		} else {
-			&[C::Ethereum, C::Polygon, C::Sepolia]
+			&[
+				C::Ethereum,
+				C::Polygon,
+				C::BnbChain,
+				C::Base,
+				C::Arbitrum,
+				C::Optimism,
+				C::PolygonZkEvm,
+				C::OpBNB,
+				C::Sepolia,
+			]
 		}
 	}
 
+	/// EVM chain ID (EIP-155).
+	pub const fn chain_id(&self) -> u64 {
+		use ChainName as C;
+		match self {
+			C::Ethereum => 1,
+			C::Polygon => 137,
+			C::BnbChain => 56,
+			C::Base => 8453,
+			C::Arbitrum => 42161,
+			C::Optimism => 10,
+			C::PolygonZkEvm => 1101,
+			C::OpBNB => 204,
+			C::Sepolia => 11155111,
+			C::Anvil => 31337,
+		}
+	}
+
+	/// Is it a testnet (or local) chain?
+	pub const fn is_testnet(&self) -> bool {
+		matches!(self, ChainName::Sepolia | ChainName::Anvil)
+	}
+
+	/// Chain from its EVM chain ID (EIP-155), if supported.
+	pub fn from_chain_id(chain_id: u64) -> Option<ChainName> {
+		Self::all(Mode::Test).iter().copied().find(|chain| chain.chain_id() == chain_id)
+	}
+
 	/// Get the gas limit (max. feasible for prediction so that the actual gas consumed is lower
 	/// than the predicted) of all the used functions (in onchain payment) for a given
 	/// coin. Although all the ERC20 tokens have same gas usage irrespective of chains. But, just
 	/// in case.
+	///
+	/// NOTE: On Arbitrum, it covers the L1 calldata cost too. So, if the L1 data fee is added
+	/// separately (i.e. via [`L1FeeParams`]), use [`Self::get_l2_gas_usage_limit`] instead.
 	pub fn get_gas_usage_limit(&self, coin: StableCoin) -> GasEstimate {
+		self.gas_usage_limit(coin, true)
+	}
+
+	/// Get the gas limit like [`Self::get_gas_usage_limit`], but excl. the L1 calldata cost (on
+	/// Arbitrum) i.e. to be added on top via [`L1FeeParams`].
+	pub fn get_l2_gas_usage_limit(&self, coin: StableCoin) -> GasEstimate {
+		self.gas_usage_limit(coin, false)
+	}
+
+	fn gas_usage_limit(&self, coin: StableCoin, incl_l1: bool) -> GasEstimate {
 		// NOTE: currently, every token is ERC20 with same code. So, the gas estimate kept same.
 		let est_gas = GasEstimate {
 			eth_transfer: 21_000,
@@ -173,6 +249,13 @@ impl ChainName {
 			transfer_from: 80_000,
 			permit_transfer_from: 120_000,
 		};
+		// Arbitrum's gas usage also covers the L1 calldata cost, so higher limits.
+		let arb_est_gas = GasEstimate {
+			eth_transfer: 50_000,
+			approve: 150_000,
+			transfer_from: 130_000,
+			permit_transfer_from: 180_000,
+		};
 		match (coin, self) {
 			// TODO: Reduce the gas limit for `approve` and `transfer_from` later on depending on
 			// the bulk users' data.
@@ -182,6 +265,39 @@ impl ChainName {
 			(StableCoin::USDT, ChainName::Polygon) => est_gas,
 			(StableCoin::USDC, ChainName::Polygon) => est_gas,
 			(StableCoin::DAI, ChainName::Polygon) => est_gas,
+			(StableCoin::USDT, ChainName::BnbChain) => est_gas,
+			(StableCoin::USDC, ChainName::BnbChain) => est_gas,
+			(StableCoin::DAI, ChainName::BnbChain) => est_gas,
+			(StableCoin::USDT, ChainName::Base) => est_gas,
+			(StableCoin::USDC, ChainName::Base) => est_gas,
+			(StableCoin::DAI, ChainName::Base) => est_gas,
+			(StableCoin::USDT, ChainName::Arbitrum) =>
+				if incl_l1 {
+					arb_est_gas
+				} else {
+					est_gas
+				},
+			(StableCoin::USDC, ChainName::Arbitrum) =>
+				if incl_l1 {
+					arb_est_gas
+				} else {
+					est_gas
+				},
+			(StableCoin::DAI, ChainName::Arbitrum) =>
+				if incl_l1 {
+					arb_est_gas
+				} else {
+					est_gas
+				},
+			(StableCoin::USDT, ChainName::Optimism) => est_gas,
+			(StableCoin::USDC, ChainName::Optimism) => est_gas,
+			(StableCoin::DAI, ChainName::Optimism) => est_gas,
+			(StableCoin::USDT, ChainName::PolygonZkEvm) => est_gas,
+			(StableCoin::USDC, ChainName::PolygonZkEvm) => est_gas,
+			(StableCoin::DAI, ChainName::PolygonZkEvm) => est_gas,
+			(StableCoin::USDT, ChainName::OpBNB) => est_gas,
+			(StableCoin::USDC, ChainName::OpBNB) => est_gas,
+			(StableCoin::DAI, ChainName::OpBNB) => est_gas,
 			(StableCoin::USDT, ChainName::Sepolia) => est_gas,
 			(StableCoin::USDC, ChainName::Sepolia) => est_gas,
 			(StableCoin::DAI, ChainName::Sepolia) => est_gas,
@@ -192,12 +308,16 @@ impl ChainName {
 	}
 
 	/// Get Permit2 Contract address for supported chain
+	///
+	/// NOTE: Permit2 is deployed via CREATE2, hence at the same address on all the chains.
 	pub fn get_permit2_sc_addr(&self) -> Address {
 		use ChainName as C;
 		let addr = address!("000000000022D473030F116dDEE9F6B43aC78BA3");
 		match self {
 			C::Ethereum => addr,
 			C::Polygon => addr,
+			C::BnbChain | C::OpBNB => addr,
+			C::Base | C::Arbitrum | C::Optimism | C::PolygonZkEvm => addr,
 			C::Sepolia | C::Anvil => addr,
 		}
 	}
@@ -317,7 +437,8 @@ impl StableCoin {
 pub enum Coin {
 	ETH,
 	POL,
-	// NOTE: For Sepolia testnet, use `Eth'
+	BNB,
+	// NOTE: For Sepolia testnet & the ETH based L2s, use `Eth'
 	USDT,
 	USDC,
 	DAI,
@@ -330,6 +451,7 @@ impl Coin {
 		match self {
 			C::ETH => 18,
 			C::POL => 18,
+			C::BNB => 18,
 			C::USDT => StableCoin::USDT.decimals(),
 			C::USDC => StableCoin::USDC.decimals(),
 			C::DAI => StableCoin::DAI.decimals(),
@@ -341,6 +463,12 @@ impl Coin {
 		match chain {
 			C::Ethereum => Coin::ETH,
 			C::Polygon => Coin::POL,
+			C::BnbChain => Coin::BNB,
+			C::Base => Coin::ETH,
+			C::Arbitrum => Coin::ETH,
+			C::Optimism => Coin::ETH,
+			C::PolygonZkEvm => Coin::ETH,
+			C::OpBNB => Coin::BNB,
 			C::Sepolia => Coin::ETH,
 			C::Anvil => Coin::ETH,
 		}
@@ -377,6 +505,16 @@ pub struct WalletBalancesByChain {
 	pub coin_details: Vec<(StableCoin, WalletBalancesByChainCoinDetails)>,
 }
 
+impl WalletBalancesByChain {
+	/// Balance of each coin on the `chain` (as queried).
+	pub fn coin_balances(&self, chain: ChainName) -> Result<Vec<(StableCoin, Amount)>, UfiError> {
+		self.coin_details
+			.iter()
+			.map(|(coin, details)| Ok((*coin, Amount::parse(&details.balance, *coin, chain)?)))
+			.collect()
+	}
+}
+
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
 pub struct WalletBalancesByChainCoinDetails {
 	/// price in USD
@@ -401,6 +539,16 @@ pub struct WalletBalancesByCoin {
 	pub chain_details: Vec<(ChainName, WalletBalancesByCoinChainDetails)>,
 }
 
+impl WalletBalancesByCoin {
+	/// Balance of the `coin` (as queried) on each chain.
+	pub fn chain_balances(&self, coin: StableCoin) -> Result<Vec<(ChainName, Amount)>, UfiError> {
+		self.chain_details
+			.iter()
+			.map(|(chain, details)| Ok((*chain, Amount::parse(&details.balance, coin, *chain)?)))
+			.collect()
+	}
+}
+
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
 pub struct WalletBalancesByCoinChainDetails {
 	/// formatted balance (in 2 decimals)
@@ -446,6 +594,93 @@ impl Default for UserProfile {
 	}
 }
 
+/* User IDs */
+
+/// Unique id of a user in UniFi.
+///
+/// ## Usage
+/// For SDK to retrieve the user's UID from API response.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct Uid(pub String);
+
+/// Payment-request id of a user.
+///
+/// Shared with the payment requesters, so that the user gets notified (on UniFi App) when
+/// anyone requests a payment. Rotate it to stop unwanted payment notifications, as the old one
+/// stops working.
+///
+/// ## Usage
+/// For SDK to retrieve the user's PID from API response.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct Pid(pub String);
+
+impl Display for Uid {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.0)
+	}
+}
+
+impl AsRef<str> for Uid {
+	fn as_ref(&self) -> &str {
+		&self.0
+	}
+}
+
+impl Display for Pid {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.0)
+	}
+}
+
+impl AsRef<str> for Pid {
+	fn as_ref(&self) -> &str {
+		&self.0
+	}
+}
+
+/* Contacts */
+
+/// A payee saved in the user's contact book.
+///
+/// ## Usage
+/// For SDK to retrieve the contact(s) from API response.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct Contact {
+	/// Unique id of the contact (generated by UniFi).
+	pub uid: String,
+	pub name: String,
+	/// Wallet address of the payee.
+	pub address: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub email: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub note: Option<String>,
+}
+
+/// Payload to add a new contact or update an existing one.
+///
+/// ## Usage
+/// For SDK to send the contact(s) via request body.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ContactPayload {
+	pub name: String,
+	/// Wallet address of the payee.
+	pub address: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub email: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub note: Option<String>,
+}
+
+impl From<Contact> for ContactPayload {
+	fn from(val: Contact) -> Self {
+		let Contact { name, address, email, note, .. } = val;
+		Self { name, address, email, note }
+	}
+}
+
 /* Currency */
 
 #[allow(non_camel_case_types)]
@@ -497,7 +732,7 @@ impl Currency {
 
 /* OC Pay history */
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
 pub struct PayHistoryFilterParams {
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub chain: Option<ChainName>,
@@ -575,6 +810,21 @@ impl OcPayReceipt {
 		"0".to_owned()
 	}
 
+	/// Paid amount in the receipt's coin (on its chain).
+	pub fn paid_amount(&self) -> Result<Amount, UfiError> {
+		Amount::parse(&self.amount, self.coin, self.chain)
+	}
+
+	/// Est. fee (shown before paying) in the receipt's coin (on its chain).
+	pub fn est_fee_amount(&self) -> Result<Amount, UfiError> {
+		Amount::parse(&self.est_fee, self.coin, self.chain)
+	}
+
+	/// Actual fee (charged) in the receipt's coin (on its chain).
+	pub fn act_fee_amount(&self) -> Result<Amount, UfiError> {
+		Amount::parse(&self.act_fee, self.coin, self.chain)
+	}
+
 	/// This fn is used to hide the savings field in the Receipt UI.
 	///
 	/// `true` => legacy receipt
@@ -631,6 +881,29 @@ impl OcPayReceiptStatus {
 	pub fn is_finalized(&self) -> bool {
 		self.eq(&Self::Finalized)
 	}
+
+	pub fn is_failed(&self) -> bool {
+		self.eq(&Self::Failed)
+	}
+
+	pub fn is_reorged(&self) -> bool {
+		self.eq(&Self::Reorged)
+	}
+
+	/// Has the payment reached the `target` status (or beyond) in its happy path i.e.
+	/// `Processing` -> `Confirmed` -> `Finalized`?
+	///
+	/// E.g. a `Finalized` payment has reached `Confirmed` too.
+	pub fn has_reached(&self, target: Self) -> bool {
+		use OcPayReceiptStatus as S;
+		match target {
+			S::Processing => self.is_processing() || self.is_confirmed() || self.is_finalized(),
+			S::Confirmed => self.is_confirmed() || self.is_finalized(),
+			S::Finalized => self.is_finalized(),
+			S::Failed => self.is_failed(),
+			S::Reorged => self.is_reorged(),
+		}
+	}
 }
 
 #[derive(
@@ -839,6 +1112,18 @@ pub struct PreOcpValues {
 	pub est_fee: String,
 }
 
+impl PreOcpValues {
+	/// Balance in the coin on the chain (as queried).
+	pub fn balance_amount(&self, payload: PreOcpPayload) -> Result<Amount, UfiError> {
+		Amount::parse(&self.balance, payload.coin, payload.chain)
+	}
+
+	/// Est. fee in the coin on the chain (as queried).
+	pub fn est_fee_amount(&self, payload: PreOcpPayload) -> Result<Amount, UfiError> {
+		Amount::parse(&self.est_fee, payload.coin, payload.chain)
+	}
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
 pub struct PreOcpValuesNcw {
 	/// Is coin allowance sufficient?
@@ -860,6 +1145,23 @@ pub struct PreOcpValuesNcw {
 	pub est_fee: String,
 }
 
+impl PreOcpValuesNcw {
+	/// Required allowance in the coin on the chain (as queried).
+	pub fn required_allowance_amount(&self, payload: PreOcpPayload) -> Result<Amount, UfiError> {
+		Amount::parse(&self.required_allowance, payload.coin, payload.chain)
+	}
+
+	/// Balance in the coin on the chain (as queried).
+	pub fn balance_amount(&self, payload: PreOcpPayload) -> Result<Amount, UfiError> {
+		Amount::parse(&self.balance, payload.coin, payload.chain)
+	}
+
+	/// Est. fee in the coin on the chain (as queried).
+	pub fn est_fee_amount(&self, payload: PreOcpPayload) -> Result<Amount, UfiError> {
+		Amount::parse(&self.est_fee, payload.coin, payload.chain)
+	}
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
 pub struct PreOcpValuesNcwParams {
 	/// Allowance to `Permit2` need to use for comparo. So, "U256" in String.
@@ -875,14 +1177,135 @@ pub struct PreOcpValuesNcwParams {
 	/// ### Usage
 	/// - required in compute est. fee (in stablecoin).
 	pub gas_price: u128,
-	/// E.g. ETH, POL, ..
+	/// Price (in USD) of the gas token e.g. ETH, POL, .. as decimal str. E.g. "3021.45"
 	/// ### Usage
 	/// - required in compute est. fee (in stablecoin).
-	pub gas_token_price: f64,
-	/// E.g. USDT, USDC, ..
+	#[serde(deserialize_with = "de_decimal_str")]
+	pub gas_token_price: String,
+	/// Price (in USD) of the coin e.g. USDT, USDC, .. as decimal str. E.g. "0.9998"
 	/// ### Usage
 	/// - required in compute est. fee (in stablecoin).
-	pub coin_price: f64,
+	#[serde(deserialize_with = "de_decimal_str")]
+	pub coin_price: String,
+	/// L1 data fee params for rollups (OP-stack, Arbitrum), where the L1 data fee dominates.
+	/// `None` for L1s.
+	/// ### Usage
+	/// - required in compute est. fee (in stablecoin) on rollups.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub l1_fee: Option<L1FeeParams>,
+}
+
+/// Decimal str from either a JSON string (kept as is) or a number e.g. `3021.45` (older payloads).
+///
+/// NOTE: A (float) number is formatted as the shortest decimal that round-trips to it, which is the
+/// decimal text it was written as (up to insignificant zeros).
+fn de_decimal_str<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+	struct DecimalStrVisitor;
+
+	impl serde::de::Visitor<'_> for DecimalStrVisitor {
+		type Value = String;
+
+		fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+			f.write_str("a decimal as string or number")
+		}
+
+		fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<String, E> {
+			Ok(v.to_owned())
+		}
+
+		fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<String, E> {
+			Ok(v.to_string())
+		}
+
+		fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<String, E> {
+			Ok(v.to_string())
+		}
+
+		fn visit_f64<E: serde::de::Error>(self, v: f64) -> Result<String, E> {
+			if !v.is_finite() {
+				return Err(E::custom(format!("invalid decimal: {}", v)));
+			}
+			// `Display` of f64 is the shortest round-trip repr. & never in scientific notation.
+			Ok(v.to_string())
+		}
+	}
+
+	deserializer.deserialize_any(DecimalStrVisitor)
+}
+
+/// Params of the L1 data fee charged by rollups for posting the tx (calldata) to L1.
+///
+/// As per the OP-stack (Ecotone) `GasPriceOracle`:
+/// ```text
+/// l1_fee = calldata_gas * (16 * l1_base_fee * base_fee_scalar + blob_base_fee * blob_base_fee_scalar)
+///          / (16 * 10^6)
+/// ```
+/// where `calldata_gas` is 4 per zero & 16 per non-zero byte of the tx.
+///
+/// NOTE: For Arbitrum, the L2 gas limit is then used i.e. [`ChainName::get_l2_gas_usage_limit`], so
+/// that the L1 calldata cost isn't counted twice. Set `base_fee_scalar` to [`Self::SCALAR_DENOM`]
+/// (i.e. 1.0) with `l1_base_fee` as its L1 price per calldata unit
+/// (`ArbGasInfo.getL1BaseFeeEstimate`) & the blob params to zero.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct L1FeeParams {
+	/// L1 base fee in wei. `GasPriceOracle.l1BaseFee`
+	pub l1_base_fee: u128,
+	/// L1 blob base fee in wei. `GasPriceOracle.blobBaseFee`
+	pub blob_base_fee: u128,
+	/// Scaled by 10^6. `GasPriceOracle.baseFeeScalar`
+	pub base_fee_scalar: u32,
+	/// Scaled by 10^6. `GasPriceOracle.blobBaseFeeScalar`
+	pub blob_base_fee_scalar: u32,
+}
+
+impl L1FeeParams {
+	/// 1.0 as scalar.
+	pub const SCALAR_DENOM: u32 = 1_000_000;
+}
+
+/// Fee terms applied on top of the network fee of an onchain payment.
+///
+/// ```text
+/// est_fee = max(network_fee * multiplier_bps / 10_000 + surcharge, min_fee)
+/// ```
+///
+/// NOTE: Amounts are in human format (e.g. "0.5") & in the paid coin, which being a stablecoin
+/// is ~ USD.
+///
+/// ## Usage
+/// - Standard terms via `FeePolicy::default()`.
+/// - Custom (e.g. enterprise plan) terms by overriding the fields of the default one.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FeePolicy {
+	/// Multiplier (in bps) on the network fee. E.g. `11_500` => 115% of the network fee.
+	pub multiplier_bps: u64,
+	/// Min. est. fee. E.g. "0.1" USDT
+	pub min_fee: String,
+	/// Fixed fee added per payment. E.g. "0.05" USDT
+	pub surcharge: String,
+	/// Safety buffer added to the required allowance, so that the payer isn't prompted to approve
+	/// again on a small change in est. fee. E.g. "10" USDT
+	pub allowance_buffer: String,
+}
+
+impl Default for FeePolicy {
+	fn default() -> Self {
+		Self {
+			multiplier_bps: Self::DEFAULT_MULTIPLIER_BPS,
+			min_fee: "0".to_owned(),
+			surcharge: "0".to_owned(),
+			allowance_buffer: Self::DEFAULT_ALLOWANCE_BUFFER.to_owned(),
+		}
+	}
+}
+
+impl FeePolicy {
+	/// 100% in bps.
+	pub const BPS_DENOM: u64 = 10_000;
+	/// Platform fee of 15% on top of the network fee.
+	pub const DEFAULT_MULTIPLIER_BPS: u64 = 11_500;
+	/// $10 as it's presumed that est. fee won't change by $10, but may be upto $1.
+	pub const DEFAULT_ALLOWANCE_BUFFER: &str = "10";
 }
 
 #[derive(
@@ -909,3 +1332,248 @@ impl Display for PayOnchainPayload {
 		write!(f, "{:?}", self)
 	}
 }
+
+impl PayOnchainPayload {
+	/// Derive the idempotency key of the payment deterministically from `user_id`, `chain`,
+	/// `coin`, `to_address`, `amount` & `memo`, so that retrying the same payment (e.g. after a
+	/// network timeout) is deduplicated by UniFi.
+	///
+	/// Cosmetic differences are normalized i.e. address case and leading/trailing zeros in
+	/// amount. E.g. "010.50" & "10.5" give the same key.
+	///
+	/// NOTE: Two intended payments with the same details would be treated as one. In that case,
+	/// set a distinct `memo` (e.g. `Memo::Custom(invoice_id)`) for each.
+	///
+	/// ## Returns
+	/// keccak256 hash as hex (without `0x`).
+	pub fn idempotency_key(&self, user_id: &str) -> String {
+		let PayOnchainPayload { chain, coin, to_address, amount, memo } = self;
+
+		let memo: String = memo.clone().into();
+		let fields = [
+			user_id.trim().to_owned(),
+			chain.to_string(),
+			coin.to_string(),
+			to_address.trim().to_lowercase(),
+			Self::normalize_amount(amount),
+			memo,
+		];
+
+		// Length prefixed, so that no 2 diff. set of fields give the same preimage.
+		let mut hasher = Keccak256::new();
+		for field in fields {
+			hasher.update((field.len() as u64).to_be_bytes());
+			hasher.update(field.as_bytes());
+		}
+		hex::encode(hasher.finalize())
+	}
+
+	fn normalize_amount(amount: &str) -> String {
+		let amount = amount.trim();
+		let (whole, frac) = amount.split_once('.').unwrap_or((amount, ""));
+		let whole = whole.trim_start_matches('0');
+		let frac = frac.trim_end_matches('0');
+
+		let whole = if whole.is_empty() { "0" } else { whole };
+		if frac.is_empty() { whole.to_owned() } else { format!("{}.{}", whole, frac) }
+	}
+}
+
+/// Test
+/// ```sh
+/// RUSTFLAGS="-Awarnings" cargo t -p unifi-sdk-primitives -F utils -- types::tests --show-output
+/// ```
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Every variant, listed via an exhaustive match. So, a new chain fails to compile here until
+	/// added.
+	fn every_chain() -> Vec<ChainName> {
+		use ChainName as C;
+		let listed = |chain: C| match chain {
+			C::Ethereum |
+			C::Polygon |
+			C::BnbChain |
+			C::Base |
+			C::Arbitrum |
+			C::Optimism |
+			C::PolygonZkEvm |
+			C::OpBNB |
+			C::Sepolia |
+			C::Anvil => chain,
+		};
+		[
+			C::Ethereum,
+			C::Polygon,
+			C::BnbChain,
+			C::Base,
+			C::Arbitrum,
+			C::Optimism,
+			C::PolygonZkEvm,
+			C::OpBNB,
+			C::Sepolia,
+			C::Anvil,
+		]
+		.map(listed)
+		.to_vec()
+	}
+
+	#[test]
+	fn chains_are_exhaustive() {
+		let chains = every_chain();
+		assert_eq!(ChainName::all(Mode::Test), chains.as_slice());
+		assert_eq!(ChainName::all(Mode::Prod), &chains[..chains.len() - 1]);
+
+		for chain in chains {
+			assert_eq!(chain.as_ref(), chain.to_string());
+			assert_eq!(chain.as_ref().parse::<ChainName>(), Ok(chain));
+			assert_eq!(chain.as_ref().to_lowercase().parse::<ChainName>(), Ok(chain));
+			assert_eq!(ChainName::from_chain_id(chain.chain_id()), Some(chain));
+			assert_eq!(
+				chain.get_permit2_sc_addr(),
+				address!("000000000022D473030F116dDEE9F6B43aC78BA3")
+			);
+			assert_eq!(Coin::chain_to_gas_coin(chain).decimals(), 18);
+			for &coin in StableCoin::all() {
+				let GasEstimate { eth_transfer, approve, transfer_from, permit_transfer_from } =
+					chain.get_gas_usage_limit(coin);
+				assert!(eth_transfer >= 21_000);
+				assert!(approve > 0 && transfer_from > 0 && permit_transfer_from > 0);
+
+				let l2 = chain.get_l2_gas_usage_limit(coin);
+				assert!(l2.permit_transfer_from <= permit_transfer_from);
+				if chain != ChainName::Arbitrum {
+					assert_eq!(l2.permit_transfer_from, permit_transfer_from);
+				}
+			}
+		}
+		assert_eq!(ChainName::from_chain_id(0), None);
+	}
+
+	#[test]
+	fn chain_ids() {
+		use ChainName as C;
+		let ids = [
+			(C::Ethereum, 1),
+			(C::Polygon, 137),
+			(C::BnbChain, 56),
+			(C::Base, 8453),
+			(C::Arbitrum, 42161),
+			(C::Optimism, 10),
+			(C::PolygonZkEvm, 1101),
+			(C::OpBNB, 204),
+			(C::Sepolia, 11155111),
+			(C::Anvil, 31337),
+		];
+		for (chain, id) in ids {
+			assert_eq!(chain.chain_id(), id, "{}", chain);
+		}
+		assert!(matches!(Coin::chain_to_gas_coin(C::BnbChain), Coin::BNB));
+		assert!(matches!(Coin::chain_to_gas_coin(C::OpBNB), Coin::BNB));
+		assert!(matches!(Coin::chain_to_gas_coin(C::Base), Coin::ETH));
+	}
+
+	/// The archived discriminants mustn't change, so that the data archived earlier still reads.
+	#[test]
+	fn archived_chain_discriminants() {
+		use ChainName as C;
+		let discriminants = [
+			(C::Ethereum, 0),
+			(C::Polygon, 1),
+			(C::Sepolia, 2),
+			(C::Anvil, 3),
+			(C::BnbChain, 4),
+			(C::Base, 5),
+			(C::Arbitrum, 6),
+			(C::Optimism, 7),
+			(C::PolygonZkEvm, 8),
+			(C::OpBNB, 9),
+		];
+		assert_eq!(discriminants.len(), every_chain().len());
+		for (chain, discriminant) in discriminants {
+			let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&chain).unwrap();
+			assert_eq!(bytes.as_slice(), [discriminant], "{}", chain);
+			assert_eq!(rkyv::from_bytes::<C, rkyv::rancor::Error>(&bytes).unwrap(), chain);
+		}
+	}
+
+	#[test]
+	fn receipt_status_has_reached() {
+		use OcPayReceiptStatus as S;
+
+		assert!(S::Finalized.has_reached(S::Confirmed));
+		assert!(S::Confirmed.has_reached(S::Confirmed));
+		assert!(!S::Processing.has_reached(S::Confirmed));
+		assert!(!S::Confirmed.has_reached(S::Finalized));
+		assert!(!S::Failed.has_reached(S::Processing));
+		assert!(!S::Reorged.has_reached(S::Confirmed));
+	}
+
+	#[test]
+	fn idempotency_key_is_deterministic() {
+		let payload = PayOnchainPayload {
+			chain: ChainName::Sepolia,
+			coin: StableCoin::USDC,
+			to_address: "0xDA741C58b3e299A8c51Aa80DF70AB2881d17499c".to_owned(),
+			amount: "10.5".to_owned(),
+			memo: Memo::General,
+		};
+		let key = payload.idempotency_key("user1");
+		assert_eq!(key.len(), 64);
+		assert_eq!(key, payload.idempotency_key("user1"));
+
+		// normalized
+		let same = PayOnchainPayload {
+			to_address: payload.to_address.to_lowercase(),
+			amount: "010.500".to_owned(),
+			..payload.clone()
+		};
+		assert_eq!(key, same.idempotency_key("user1"));
+
+		// distinct
+		assert_ne!(key, payload.idempotency_key("user2"));
+		assert_ne!(
+			key,
+			PayOnchainPayload { amount: "10.05".to_owned(), ..payload.clone() }
+				.idempotency_key("user1")
+		);
+		assert_ne!(
+			key,
+			PayOnchainPayload { memo: Memo::Custom("INV-1".to_owned()), ..payload }
+				.idempotency_key("user1")
+		);
+	}
+
+	#[test]
+	fn deserializes_numeric_prices() {
+		// older payloads with the prices as numbers
+		let json = r#"{
+			"allowance": "0",
+			"balance": "10.000000",
+			"gas_price": 1000000000,
+			"gas_token_price": 3021.45,
+			"coin_price": 1
+		}"#;
+		let params = serde_json::from_str::<PreOcpValuesNcwParams>(json).unwrap();
+		assert_eq!((params.gas_token_price.as_str(), params.coin_price.as_str()), ("3021.45", "1"));
+		assert_eq!(params.l1_fee, None);
+
+		let json = json
+			.replace("3021.45", "0.000123456789012345")
+			.replace(": 1\n", ": \"0.9998\"\n");
+		let params = serde_json::from_str::<PreOcpValuesNcwParams>(&json).unwrap();
+		assert_eq!(params.gas_token_price, "0.000123456789012345");
+		assert_eq!(params.coin_price, "0.9998");
+
+		// round-trips as strings
+		let serialized = serde_json::to_string(&params).unwrap();
+		assert!(serialized.contains(r#""coin_price":"0.9998""#));
+		assert_eq!(serde_json::from_str::<PreOcpValuesNcwParams>(&serialized).unwrap(), params);
+
+		assert!(
+			serde_json::from_str::<PreOcpValuesNcwParams>(&json.replace(r#""0.9998""#, "true"))
+				.is_err()
+		);
+	}
+}
@@ -1,18 +1,26 @@
 use crate::{
+	amount::Amount,
+	decimal::{DecimalParser, Rounding},
 	errors::UfiError,
-	types::{Coin, GasEstimate, PreOcpPayload, PreOcpValuesNcwParams, StableCoin},
-};
-use alloy_primitives::{
-	Address, U256,
-	utils::{format_units, parse_units},
+	evm::{calldata_approve, calldata_gas},
+	permit2::{BundlePayV2, ISignatureTransfer},
+	types::{
+		ChainName, Coin, FeePolicy, GasEstimate, L1FeeParams, PreOcpPayload, PreOcpValuesNcwParams,
+		StableCoin,
+	},
 };
+use alloy_primitives::{Address, Bytes, U256, U512, utils::format_units};
+use alloy_sol_types::SolCall;
 use eyre::{Context, OptionExt, ensure};
 use std::str::FromStr;
 
-/// Format any num (in U256 String) to Decimal formatted considering coin's decimals.
-pub fn fmt_value(num_in_u256_str: &str, coin: StableCoin) -> eyre::Result<String> {
-	let value = format_units(U256::from_str(num_in_u256_str)?, coin.decimals())?;
-	Ok(value)
+/// Format any num (in U256 String) to Decimal formatted considering coin's decimals on the chain.
+pub fn fmt_value(
+	num_in_u256_str: &str,
+	coin: StableCoin,
+	chain: ChainName,
+) -> eyre::Result<String> {
+	Ok(Amount::from_raw_str(num_in_u256_str, coin, chain)?.to_string())
 }
 
 pub fn is_value_gte(num_in_u256_str: &str, amount: &str, coin_decimals: u8) -> eyre::Result<bool> {
@@ -34,51 +42,23 @@ pub fn sanitize_address(address: &str) -> bool {
 /// ## Usage
 /// - User entering amount as String compared to fetched net Onchain balance (U256) as validation.
 ///
-/// NOTE: All tests inside Base layer.
+/// NOTE: See [`DecimalParser`] for the accepted formats & errors.
 ///
 /// ## Arguments
 /// - use_in_ui:
 ///   - `false`: for precision during math calculation, value might be much bigger than coin
-///     decimals.
+///     decimals. So, the excess decimals are truncated.
 ///   - `true`: Usage inside web app, to show err when value's decimals > coin_decimals.
 pub fn parse_human_fmt_to_u256(
 	value: &str,
 	coin_decimals: u8,
 	use_in_ui: bool,
 ) -> eyre::Result<U256> {
-	// NOTE: this line added due to a small case where in `pending_amount` field in DB is set to
-	// "0E-18" instead of "0.00000000...000" for DAI. It was found that this was done inside
-	// `execute_bundle` fn during u128 arithmetic at mongoDB level that can't be controlled from
-	// code here. So, covered the case. Else, there would be invalid digit error in case of DAI or
-	// any token with such big decimals (7-18). Didn't notice this issue in case of 6 decimals
-	// tokens like USDT, USDC.
-	let num = if value.starts_with("0E-") { "0.0" } else { value };
-
-	// Split the input into whole and fractional parts
-	let parts: Vec<&str> = num.split('.').collect();
-
-	// Parse and scale the fractional part
-	let fractional_part = if let Some(frac) = parts.get(1) {
-		if use_in_ui {
-			ensure!(
-				frac.len() <= coin_decimals as usize,
-				UfiError::MaxDecimalsReached(coin_decimals)
-			);
-		}
-		let scale = 10u128.pow(frac.len() as u32);
-		let frac_num = frac.parse::<u128>().unwrap_or(0); // Parse fractional digits as integer
-		U256::from(frac_num) * U256::from(10u128.pow(coin_decimals as u32)) / U256::from(scale)
-	} else {
-		U256::ZERO
-	};
-
-	// Parse the whole part
-	let whole_part =
-		if let Some(whole) = parts.first() { whole.parse::<U256>()? } else { U256::ZERO };
-
-	// Scale the whole part and combine with fractional part
-	let scaled_whole_part = whole_part * U256::from(10u128.pow(coin_decimals as u32));
-	Ok(scaled_whole_part + fractional_part)
+	// NOTE: `pending_amount` field in DB might be set as "0E-18" (instead of "0.00000000...000")
+	// for DAI during u128 arithmetic at mongoDB level. The parser handles scientific notation in
+	// general.
+	let rounding = if use_in_ui { Rounding::Reject } else { Rounding::Truncate };
+	Ok(DecimalParser::new(coin_decimals).rounding(rounding).parse(value)?)
 }
 
 /// Compute Est. fee for NC Pay.
@@ -98,11 +78,14 @@ pub fn parse_human_fmt_to_u256(
 ///   - fee_excl => then parse entered amount. So, amount parsed.
 ///   - fee_incl => then parse entered amount + est_fee. So, (amount + est_fee) parsed.
 /// - `pre_ocp_values`: params (from fn: `prefetch_ncw_balance_fee_params`) for calculating est fees
-///   synchronously.
+///   synchronously. On rollups, with `l1_fee` set, the L1 data fee of the payment (& approval) tx
+///   is added to the network fee.
 /// - `is_fee_incl`
 /// - `use_is_ui`: When used in UI, we skip the `InsufficientBalance` check bcoz we don't want the
 ///   UI to collapse showing Error card. But, if you are running an example program, then you should
 ///   set to `false`, bcoz then the code returns early as `InsufficientBalance`.
+/// - `fee_policy`: fee terms (multiplier, min. fee, surcharge) on top of the network fee. Use
+///   `FeePolicy::default()` for the standard terms.
 ///
 /// There are 2 pages: ApiPlan, FliQPay, where we might set it to false, as we would want the
 /// "InsufficientBalance" to be checked before making the payment. Actually, in these pages, we
@@ -123,6 +106,7 @@ pub fn compute_est_fee_ncw(
 	pre_ocp_values: &PreOcpValuesNcwParams,
 	is_fee_incl: bool,
 	use_in_ui: bool,
+	fee_policy: &FeePolicy,
 ) -> eyre::Result<(bool, String, String)> {
 	// 1. Destructure and Parse Inputs immediately
 	let PreOcpPayload { coin, chain } = payload;
@@ -132,9 +116,10 @@ pub fn compute_est_fee_ncw(
 		gas_price,
 		gas_token_price,
 		coin_price,
+		l1_fee,
 	} = pre_ocp_values;
 
-	let coin_decimals = coin.decimals();
+	let coin_decimals = coin.decimals_on(chain)?;
 	let allowance = U256::from_str(allowance_str).wrap_err("Failed to parse allowance")?;
 	let mut tot_amount = parse_human_fmt_to_u256(amt_or_tot_amount, coin_decimals, false)
 		.wrap_err("Failed to parse amount")?;
@@ -151,12 +136,23 @@ pub fn compute_est_fee_ncw(
 	}
 
 	// 3. Pre-calculate Constants
-	let GasEstimate { approve, permit_transfer_from, .. } = chain.get_gas_usage_limit(coin);
-
-	// Optimization: Calculate price denominator once.
-	// Formula: coin_price * 10^(gas_coin_decimals)
-	let gas_coin_decimals = Coin::chain_to_gas_coin(chain).decimals() as i32;
-	let price_denom = coin_price * 10f64.powi(gas_coin_decimals);
+	// NOTE: With the L1 data fee added separately, excl. it from the gas limit (on Arbitrum).
+	let GasEstimate { approve, permit_transfer_from, .. } = match l1_fee {
+		Some(_) => chain.get_l2_gas_usage_limit(coin),
+		None => chain.get_gas_usage_limit(coin),
+	};
+	let gas_coin_decimals = Coin::chain_to_gas_coin(chain).decimals();
+	let price_parser = DecimalParser::new(PRICE_DECIMALS);
+	let gas_token_price = price_parser
+		.parse(gas_token_price)
+		.wrap_err("Failed to parse gas token price")?;
+	let coin_price = price_parser.parse(coin_price).wrap_err("Failed to parse coin price")?;
+	ensure!(!coin_price.is_zero(), "Coin price can't be zero");
+	let FeePolicy { multiplier_bps, min_fee, surcharge, .. } = fee_policy;
+	let min_fee = parse_human_fmt_to_u256(min_fee, coin_decimals, false)
+		.wrap_err("Failed to parse min. fee")?;
+	let surcharge = parse_human_fmt_to_u256(surcharge, coin_decimals, false)
+		.wrap_err("Failed to parse surcharge")?;
 
 	// 4. Define Calculation Logic (Closure to handle repetition)
 	// Returns: (Calculated Fee U256, Required allowance val)
@@ -170,13 +166,33 @@ pub fn compute_est_fee_ncw(
 			(approve + permit_transfer_from, target_amt - allowance)
 		};
 
-		// NOTE: Add platform_fee on top of network fee.
-		let platform_payment_fee_multiplier = 1.15;
-		// fee = (gas_usage * gas_price * gas_token_price) / (coin_price * 10^decimals)
-		let est_gas_fee = (est_gas_usage * gas_price) as f64;
-		let est_fee_f64 =
-			est_gas_fee * gas_token_price * platform_payment_fee_multiplier / price_denom;
-		let est_fee_u256 = parse_human_fmt_to_u256(&est_fee_f64.to_string(), coin_decimals, false)?;
+		// NOTE: Both already incl. the markup i.e. `multiplier_bps`.
+		let network_fee_with_markup = est_fee_in_coin(
+			est_gas_usage,
+			*gas_price,
+			gas_token_price,
+			coin_price,
+			*multiplier_bps,
+			gas_coin_decimals,
+			coin_decimals,
+		)?;
+		let l1_fee_with_markup = match l1_fee {
+			Some(params) => est_fee_in_coin(
+				1,
+				l1_data_fee(params, chain.get_permit2_sc_addr(), !is_suff)?,
+				gas_token_price,
+				coin_price,
+				*multiplier_bps,
+				gas_coin_decimals,
+				coin_decimals,
+			)?,
+			None => U256::ZERO,
+		};
+		let est_fee_u256 = network_fee_with_markup
+			.checked_add(l1_fee_with_markup)
+			.and_then(|fee| fee.checked_add(surcharge))
+			.ok_or_eyre("Calculation error: est. fee overflow")?
+			.max(min_fee);
 
 		Ok((est_fee_u256, required_allowance_val))
 	};
@@ -204,12 +220,105 @@ pub fn compute_est_fee_ncw(
 	Ok((is_suff, required_allowance_val_fmt, est_fee_fmt))
 }
 
+/// Decimals the prices (in USD) are parsed with i.e. kept exactly as `price * 10^PRICE_DECIMALS`.
+///
+/// NOTE: A price with more (non-zero) decimals errs, instead of losing precision.
+const PRICE_DECIMALS: u8 = 18;
+
+/// Est. fee (in coin's smallest unit) for the gas usage, computed in fixed-point & rounded down.
+///
+/// ```text
+/// est_fee = gas_usage * gas_price * gas_token_price * multiplier_bps / 10_000
+///           * 10^coin_decimals / (coin_price * 10^gas_coin_decimals)
+/// ```
+///
+/// ## Arguments
+/// - `gas_token_price`, `coin_price`: in [`PRICE_DECIMALS`]. As both have the same scale, it
+///   cancels out in their ratio.
+fn est_fee_in_coin(
+	gas_usage: u128,
+	gas_price: u128,
+	gas_token_price: U256,
+	coin_price: U256,
+	multiplier_bps: u64,
+	gas_coin_decimals: u8,
+	coin_decimals: u8,
+) -> eyre::Result<U256> {
+	let pow10 = |exp: u8| U512::from(10).pow(U512::from(exp));
+
+	// NOTE: U512, so that the product of 2 U256 (prices) & the rest never overflows.
+	let numerator = [
+		U512::from(gas_usage),
+		U512::from(gas_price),
+		U512::from(gas_token_price),
+		U512::from(multiplier_bps),
+		pow10(coin_decimals),
+	]
+	.into_iter()
+	.try_fold(U512::from(1), |acc, x| acc.checked_mul(x))
+	.ok_or_eyre("Calculation error: est. fee overflow")?;
+	let denominator =
+		[U512::from(coin_price), U512::from(FeePolicy::BPS_DENOM), pow10(gas_coin_decimals)]
+			.into_iter()
+			.try_fold(U512::from(1), |acc, x| acc.checked_mul(x))
+			.ok_or_eyre("Calculation error: est. fee overflow")?;
+
+	U256::checked_from_limbs_slice((numerator / denominator).as_limbs())
+		.ok_or_eyre("Calculation error: est. fee overflow")
+}
+
+/// Size (in bytes) of a signed EIP-1559 tx excluding the calldata i.e. chain id, nonce, fees, gas
+/// limit, to, value, access list & signature.
+const TX_ENVELOPE_SIZE: u64 = 110;
+
+/// L1 data fee (in wei) of the NC pay tx (& the approval tx to `permit2`, if `with_approve`) on a
+/// rollup. See [`L1FeeParams`].
+fn l1_data_fee(params: &L1FeeParams, permit2: Address, with_approve: bool) -> eyre::Result<u128> {
+	let L1FeeParams { l1_base_fee, blob_base_fee, base_fee_scalar, blob_base_fee_scalar } = *params;
+
+	let mut gas = calldata_gas(&ncw_pay_calldata()) + TX_ENVELOPE_SIZE * 16;
+	if with_approve {
+		gas += calldata_gas(&calldata_approve(permit2, U256::MAX)) + TX_ENVELOPE_SIZE * 16;
+	}
+
+	// NOTE: can't overflow as the operands are at most 64 + 4 + 128 + 32 bits.
+	let fee_per_gas = U256::from(16) * U256::from(l1_base_fee) * U256::from(base_fee_scalar) +
+		U256::from(blob_base_fee) * U256::from(blob_base_fee_scalar);
+	let fee = U256::from(gas) * fee_per_gas / U256::from(16 * L1FeeParams::SCALAR_DENOM as u64);
+	u128::try_from(fee).wrap_err("Calculation error: L1 data fee overflow")
+}
+
+/// Calldata of a typical NC pay i.e. `BundlePayV2.batchPaymentSSPermit` with a single payment.
+///
+/// NOTE: Only its size (zero & non-zero bytes) matters, so the values are representative.
+fn ncw_pay_calldata() -> Vec<u8> {
+	let token = Address::repeat_byte(0x11);
+	let amount = U256::from(1_000_000_000u64);
+	BundlePayV2::batchPaymentSSPermitCall {
+		relayerIndex: 1,
+		pay: BundlePayV2::BatchPaymentSSPermit {
+			from: Address::repeat_byte(0x22),
+			permit: ISignatureTransfer::PermitBatchTransferFrom {
+				permitted: vec![ISignatureTransfer::TokenPermissions { token, amount }],
+				nonce: U256::from(u64::MAX),
+				deadline: U256::from(u32::MAX),
+			},
+			transferDetails: vec![ISignatureTransfer::SignatureTransferDetails {
+				to: Address::repeat_byte(0x33),
+				requestedAmount: amount,
+			}],
+			signature: Bytes::from([0x44; 65]),
+		},
+	}
+	.abi_encode()
+}
+
 /// Get required allowance value (considering practical case).
 ///
 /// NOTE: If user opts for approving min. amount (instead of `U256::MAX`), then instead of exact
-/// `total_spend`, user is asked to approve `total_spend + $10`, so that due to change in network
-/// fee, thereby est. fee, user is not prompted to approve again. Here, it's presumed that est. fee
-/// won't change by $10, but may be upto $1.
+/// `total_spend`, user is asked to approve `total_spend + allowance_buffer` (default: $10), so
+/// that due to change in network fee, thereby est. fee, user is not prompted to approve again.
+/// Here, it's presumed that est. fee won't change by $10, but may be upto $1.
 ///
 /// ## Usage
 /// - After the payment page loads, bal & est. fees successfully loaded, then in case of NC, update
@@ -218,27 +327,25 @@ pub fn compute_est_fee_ncw(
 ///
 /// ## Arguments
 /// - `amount`: entered amount
-/// - `coin`
 /// - `allowance`: last fetched coin allowance in U256 string.
-/// - `est_fee`: last fetched est_fee
+/// - `est_fee`: last fetched est_fee (of the same coin on the same chain as `amount`)
 /// - `is_fee_incl`
+/// - `fee_policy`: for the allowance buffer.
 ///
 /// ## Returns
-/// in decimals E.g. "10.124" USDT
+/// in the coin's decimals E.g. "10.124000" USDT
 pub fn update_req_allowance(
-	amount: &str,
-	coin: StableCoin,
+	amount: &Amount,
 	allowance: &str,
-	est_fee: &str,
+	est_fee: &Amount,
 	is_fee_incl: bool,
-) -> eyre::Result<String> {
-	let coin_decimals = coin.decimals();
-	let amount_u256 = parse_human_fmt_to_u256(amount, coin_decimals, false)?;
-	let est_fee_u256 = parse_human_fmt_to_u256(est_fee, coin_decimals, false)?;
-
-	let mut total_spend = if is_fee_incl { amount_u256 } else { amount_u256 + est_fee_u256 };
-	// NOTE: add $10 as safety val, so as to avoid repetitive approval prompt.
-	total_spend += U256::from(10_u128) * U256::from(10).pow(U256::from(coin_decimals));
+	fee_policy: &FeePolicy,
+) -> eyre::Result<Amount> {
+	let mut total_spend = total_spend(amount, est_fee, is_fee_incl)?.raw();
+	// NOTE: add buffer as safety val, so as to avoid repetitive approval prompt.
+	total_spend += DecimalParser::new(amount.decimals())
+		.parse(&fee_policy.allowance_buffer)
+		.wrap_err("Failed to parse allowance buffer")?;
 
 	let allowance = U256::from_str(allowance).wrap_err("Failed to parse allowance")?;
 	let req_allowance = if total_spend.gt(&allowance) {
@@ -277,7 +384,7 @@ pub fn update_req_allowance(
 		U256::ZERO
 	};
 
-	fmt_output(req_allowance, coin_decimals)
+	Ok(Amount::with_decimals(req_allowance, amount.coin(), amount.decimals()))
 }
 
 /// Use this instead of `format_units` as it has diff. Err type. \
@@ -288,29 +395,20 @@ pub fn fmt_output(value: U256, coin_decimals: u8) -> eyre::Result<String> {
 
 /// Get total spend
 ///
-/// ```
+/// ```text
 /// total_spend = amount + est_fee
 /// ```
 ///
 /// ## Usage
 /// - In Web app, this is shown to
-pub fn total_spend(
-	amount: &str,
-	est_fee: &str,
-	coin: StableCoin,
-	is_fee_incl: bool,
-) -> eyre::Result<String> {
+///
+/// NOTE: Errs if `amount` & `est_fee` aren't of the same coin (on the same chain).
+pub fn total_spend(amount: &Amount, est_fee: &Amount, is_fee_incl: bool) -> eyre::Result<Amount> {
 	if is_fee_incl {
-		return Ok(amount.to_owned())
+		return Ok(*amount)
 	}
 
-	let coin_decimals = coin.decimals();
-	let amt_u256 = parse_human_fmt_to_u256(amount, coin_decimals, false)?;
-	let estfee_u256 = parse_human_fmt_to_u256(est_fee, coin_decimals, false)?;
-
-	let total_spend = amt_u256 + estfee_u256;
-
-	fmt_output(total_spend, coin_decimals)
+	Ok(amount.checked_add(est_fee)?)
 }
 
 /// Validates the amount string and converts it to `U256`.
@@ -320,81 +418,43 @@ pub fn total_spend(
 /// - In base layer, OCP for sanitizing input
 /// - In SDK layer, OCP for sanitizing input using `sanitize_and_parse_amount.is_ok()` if value not
 ///   required. Ideally we need the value in U256 to compare with fetched balance & est fees.
-pub fn sanitize_and_parse_amount(amount: &str, coin: StableCoin) -> eyre::Result<U256> {
-	let amount_u256 = parse_human_fmt_to_u256(amount, coin.decimals(), true)?;
-	ensure!(!amount_u256.is_zero(), UfiError::ZeroAmount);
-	Ok(amount_u256)
+pub fn sanitize_and_parse_amount(
+	amount: &str,
+	coin: StableCoin,
+	chain: ChainName,
+) -> eyre::Result<U256> {
+	let amount = Amount::parse(amount, coin, chain)?;
+	ensure!(!amount.is_zero(), UfiError::ZeroAmount);
+	Ok(amount.raw())
 }
 
 /// Validates the amount (with est. fee) against the user's balance.
 ///
-/// ## Arguments
-/// - amount: E.g. "1.02345".
-/// - ..
-///
 /// ## Notes
-/// - balance & est_fee, is_allowance_zero are fetched via fn `fetch_pre_ocp_balance_and_est_fee`.
+/// - balance & est_fee are fetched via fn `fetch_pre_ocp_balance_and_est_fee` & can be typed via
+///   `PreOcpValues::{balance_amount, est_fee_amount}`.
 /// - In the FE as these are already fetched once & values shown in UI, we don't need to fetch again
 ///   inside the fn. That's why we are using the fetched values.
 ///
 /// ## Returns
-/// - if Ok(true), then valid
-/// - if Ok(false), then "Insuficient balance"
-/// - if Err(err), then "Max 6 decimals places allowed \nPlease enter a valid amount."
-pub fn validate_and_parse_amount(
-	amount: &str,
-	coin: StableCoin,
-	balance: &str,
-	est_fee: &str,
-	is_fee_incl: bool,
-) -> eyre::Result<()> {
-	let amount_u256 = sanitize_and_parse_amount(amount, coin)?;
-	let balance_u256: U256 = parse_units(balance, coin.decimals())?.into();
-	let est_fee_u256: U256 = parse_units(est_fee, coin.decimals())?.into();
-
-	let total_amount_u256 = if is_fee_incl {
-		amount_u256
-	} else {
-		amount_u256.checked_add(est_fee_u256).ok_or_eyre(
-			"Calculation error: Failed to add amount and estimated fees — possible overflow",
-		)?
-	};
-
-	ensure!(total_amount_u256.le(&balance_u256), UfiError::InsufficientBalance);
-
-	Ok(())
-}
-
-/// Validate and parse amount without sanitization.
-///
-/// ## Arguments
-/// - amount: U256 string i.e. use "102345" for "1.02345" USDT.
-/// - ..
-///
-/// ## Usage
-/// - FliQPay page where we separately sanitize the amount synchronously
-pub fn validate_and_parse_amount_wo_sanitize(
-	amount: &str,
-	coin: StableCoin,
-	balance: &str,
-	est_fee: &str,
+/// - if Ok(()), then valid
+/// - if Err(UfiError::ZeroAmount | UfiError::InsufficientBalance), then invalid
+/// - if Err(UfiError::CoinMismatch | UfiError::DecimalsMismatch), then the amounts aren't of the
+///   same coin (on the same chain)
+pub fn validate_amount(
+	amount: &Amount,
+	balance: &Amount,
+	est_fee: &Amount,
 	is_fee_incl: bool,
 ) -> eyre::Result<()> {
-	let amount_u256 = amount.parse::<U256>()?;
-	let balance_u256: U256 = parse_units(balance, coin.decimals())?.into();
-	let est_fee_u256: U256 = parse_units(est_fee, coin.decimals())?.into();
+	ensure!(!amount.is_zero(), UfiError::ZeroAmount);
 
-	let total_amount_u256 = if is_fee_incl {
-		amount_u256
-	} else {
-		amount_u256.checked_add(est_fee_u256).ok_or_eyre(
-			"Calculation error: Failed to add amount and estimated fees — possible overflow",
-		)?
-	};
-
-	ensure!(total_amount_u256.le(&balance_u256), UfiError::InsufficientBalance);
-
-	Ok(())
+	let total_amount = if is_fee_incl { *amount } else { amount.checked_add(est_fee)? };
+	match balance.checked_sub(&total_amount) {
+		Ok(_) => Ok(()),
+		Err(UfiError::NegativeAmount) => Err(UfiError::InsufficientBalance.into()),
+		Err(e) => Err(e.into()),
+	}
 }
 
 /// Calculate the no. of worker threads per CPU
@@ -414,27 +474,289 @@ pub fn calculate_worker_threads() -> usize {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use bigdecimal::{BigDecimal, RoundingMode};
+	use proptest::prelude::*;
+
+	/// Reference est. fee (in coin's smallest unit) in big-decimal.
+	fn ref_est_fee(
+		gas_usage: u128,
+		gas_price: u128,
+		gas_token_price: &str,
+		coin_price: &str,
+		gas_coin_decimals: u8,
+		coin_decimals: u8,
+	) -> String {
+		let fee = BigDecimal::from(gas_usage) *
+			BigDecimal::from(gas_price) *
+			BigDecimal::from_str(gas_token_price).unwrap() *
+			BigDecimal::from_str("1.15").unwrap() /
+			(BigDecimal::from_str(coin_price).unwrap() *
+				BigDecimal::new(1.into(), -(gas_coin_decimals as i64)));
+		let fee = fee * BigDecimal::new(1.into(), -(coin_decimals as i64));
+		fee.with_scale_round(0, RoundingMode::Down).to_string()
+	}
+
+	/// Decimal str of `mantissa / 10^scale`.
+	fn decimal_str(mantissa: u64, scale: usize) -> String {
+		let digits = format!("{:0>width$}", mantissa, width = scale + 1);
+		let (whole, frac) = digits.split_at(digits.len() - scale);
+		if frac.is_empty() { whole.to_owned() } else { format!("{}.{}", whole, frac) }
+	}
+
+	proptest! {
+		#[test]
+		fn est_fee_matches_big_decimal(
+			gas_usage in 21_000u128..2_000_000,
+			gas_price in 1u128..10u128.pow(15),
+			(gas_token_price, gas_token_price_scale) in (0u64..u64::MAX, 0usize..=18),
+			(coin_price, coin_price_scale) in (1u64..u64::MAX, 0usize..=18),
+			gas_coin_decimals in prop::sample::select(vec![6u8, 18]),
+			coin_decimals in prop::sample::select(vec![6u8, 18]),
+		) {
+			let gas_token_price = decimal_str(gas_token_price, gas_token_price_scale);
+			let coin_price = decimal_str(coin_price, coin_price_scale);
+
+			let fee = est_fee_in_coin(
+				gas_usage,
+				gas_price,
+				DecimalParser::new(PRICE_DECIMALS).parse(&gas_token_price).unwrap(),
+				DecimalParser::new(PRICE_DECIMALS).parse(&coin_price).unwrap(),
+				FeePolicy::DEFAULT_MULTIPLIER_BPS,
+				gas_coin_decimals,
+				coin_decimals,
+			)
+			.unwrap();
+			let ref_fee = ref_est_fee(
+				gas_usage,
+				gas_price,
+				&gas_token_price,
+				&coin_price,
+				gas_coin_decimals,
+				coin_decimals,
+			);
+			prop_assert_eq!(fee.to_string(), ref_fee);
+		}
+	}
 
 	#[test]
-	fn req_allowance_tests() {
-		// Test with USDT token
+	fn computes_est_fee_ncw() {
+		let policy = FeePolicy::default();
+		let payload = PreOcpPayload { coin: StableCoin::USDT, chain: ChainName::Ethereum };
+		let params = PreOcpValuesNcwParams {
+			allowance: "0".to_owned(),
+			balance: "100".to_owned(),
+			// 10 gwei
+			gas_price: 10_000_000_000,
+			gas_token_price: "3000".to_owned(),
+			coin_price: "1".to_owned(),
+			l1_fee: None,
+		};
+
+		// (100_000 + 120_000) * 10 gwei * $3000 * 1.15
+		assert_eq!(
+			compute_est_fee_ncw(payload, "10", &params, true, false, &policy).unwrap(),
+			(false, "10.000000".to_owned(), "7.590000".to_owned())
+		);
+		// 120_000 * 10 gwei * $3000 * 1.15
+		let params = PreOcpValuesNcwParams { allowance: U256::MAX.to_string(), ..params };
 		assert_eq!(
-			update_req_allowance("1", StableCoin::USDT, "0", "0.112192", false).unwrap(),
-			"11.112192"
+			compute_est_fee_ncw(payload, "10", &params, false, false, &policy).unwrap(),
+			(true, "0.00".to_owned(), "4.140000".to_owned())
 		);
+
+		// tiny fee, rounded down to the coin's decimals
+		let params = PreOcpValuesNcwParams {
+			gas_price: 1,
+			gas_token_price: "0.0000001".to_owned(),
+			..params
+		};
 		assert_eq!(
-			update_req_allowance("1", StableCoin::USDT, "0", "0.112192", true).unwrap(),
-			"11.000000"
+			compute_est_fee_ncw(payload, "10", &params, false, false, &policy).unwrap().2,
+			"0.00"
 		);
 
+		let params = PreOcpValuesNcwParams { coin_price: "0".to_owned(), ..params };
+		assert!(compute_est_fee_ncw(payload, "10", &params, false, false, &policy).is_err());
+	}
+
+	#[test]
+	fn uses_chain_decimals() {
+		let policy = FeePolicy::default();
+		let (coin, chain) = (StableCoin::USDT, ChainName::BnbChain);
+
+		assert_eq!(fmt_value("1500000", coin, ChainName::Ethereum).unwrap(), "1.500000");
+		assert_eq!(fmt_value("1500000", coin, chain).unwrap(), "0.000000000001500000");
+		assert_eq!(
+			sanitize_and_parse_amount("1.5", coin, chain).unwrap(),
+			U256::from(15) * U256::from(10).pow(U256::from(17))
+		);
+		let amount = |v| Amount::parse(v, coin, chain).unwrap();
+		assert_eq!(
+			total_spend(&amount("1"), &amount("0.5"), false).unwrap().to_string(),
+			"1.500000000000000000"
+		);
+		assert_eq!(
+			update_req_allowance(&amount("1"), "0", &amount("0.5"), false, &policy)
+				.unwrap()
+				.to_string(),
+			"11.500000000000000000"
+		);
+		// USDT of diff. decimals (on Ethereum)
+		let usdt_eth = Amount::parse("0.5", coin, ChainName::Ethereum).unwrap();
+		assert!(total_spend(&amount("1"), &usdt_eth, false).is_err());
+
+		let params = PreOcpValuesNcwParams {
+			allowance: U256::MAX.to_string(),
+			balance: "100".to_owned(),
+			// 1 gwei
+			gas_price: 1_000_000_000,
+			gas_token_price: "600".to_owned(),
+			coin_price: "1".to_owned(),
+			l1_fee: None,
+		};
+		// 120_000 * 1 gwei * $600 * 1.15
+		assert_eq!(
+			compute_est_fee_ncw(PreOcpPayload { coin, chain }, "10", &params, true, false, &policy)
+				.unwrap(),
+			(true, "0.00".to_owned(), "0.082800000000000000".to_owned())
+		);
+
+		// USDC isn't on opBNB
+		let (coin, chain) = (StableCoin::USDC, ChainName::OpBNB);
+		assert!(fmt_value("1500000", coin, chain).is_err());
+		assert!(sanitize_and_parse_amount("1.5", coin, chain).is_err());
+		let payload = PreOcpPayload { coin, chain };
+		assert!(compute_est_fee_ncw(payload, "10", &params, true, false, &policy).is_err());
+	}
+
+	#[test]
+	fn adds_l1_data_fee() {
+		let policy = FeePolicy::default();
+		let payload = PreOcpPayload { coin: StableCoin::USDC, chain: ChainName::Base };
+		// 612 bytes calldata (+ 110 bytes envelope) => 6104 calldata gas
+		assert_eq!(calldata_gas(&ncw_pay_calldata()), 4344);
+		let params = PreOcpValuesNcwParams {
+			allowance: U256::MAX.to_string(),
+			balance: "100".to_owned(),
+			// 0.01 gwei
+			gas_price: 10_000_000,
+			gas_token_price: "3000".to_owned(),
+			coin_price: "1".to_owned(),
+			l1_fee: None,
+		};
+		let est_fee = |payload, params: &PreOcpValuesNcwParams| {
+			compute_est_fee_ncw(payload, "10", params, true, false, &policy).unwrap().2
+		};
+		// 120_000 * 0.01 gwei * $3000 * 1.15
+		assert_eq!(est_fee(payload, &params), "0.004140");
+
+		// OP-stack (Base) scalars
+		let l1_fee = L1FeeParams {
+			l1_base_fee: 10_000_000_000,
+			blob_base_fee: 1_000_000_000,
+			base_fee_scalar: 2269,
+			blob_base_fee_scalar: 1_055_762,
+		};
+		let params = PreOcpValuesNcwParams { l1_fee: Some(l1_fee.clone()), ..params };
+		// + 6104 * (16 * 10 gwei * 2269 + 1 gwei * 1055762) / (16 * 10^6) * $3000 * 1.15
+		assert_eq!(est_fee(payload, &params), "0.006007");
+		// + approval tx: 68 bytes calldata (+ 110 bytes envelope) => 2644 calldata gas
+		let params_approve = PreOcpValuesNcwParams { allowance: "0".to_owned(), ..params.clone() };
+		assert_eq!(est_fee(payload, &params_approve), "0.010266");
+
+		// Arbitrum: L1 price per calldata unit
+		let payload = PreOcpPayload { chain: ChainName::Arbitrum, ..payload };
+		let l1_fee = L1FeeParams {
+			l1_base_fee: 10_000_000_000,
+			base_fee_scalar: L1FeeParams::SCALAR_DENOM,
+			..Default::default()
+		};
+		let params = PreOcpValuesNcwParams { l1_fee: Some(l1_fee), ..params };
+		// L2 gas limit (excl. the L1 calldata cost) i.e. 120_000 instead of 180_000
+		// 120_000 * 0.01 gwei * $3000 * 1.15 + 6104 * 10 gwei * $3000 * 1.15
+		assert_eq!(est_fee(payload, &params), "0.214728");
+		// w/o the L1 fee params, the gas limit covers the L1 calldata cost
+		let params = PreOcpValuesNcwParams { l1_fee: None, ..params };
+		// 180_000 * 0.01 gwei * $3000 * 1.15
+		assert_eq!(est_fee(payload, &params), "0.006210");
+	}
+
+	#[test]
+	fn req_allowance_tests() {
+		let policy = FeePolicy::default();
+		let req_allowance = |coin, allowance, is_fee_incl| {
+			let amount = |v| Amount::parse(v, coin, ChainName::Ethereum).unwrap();
+			update_req_allowance(&amount("1"), allowance, &amount("0.112192"), is_fee_incl, &policy)
+				.unwrap()
+				.to_string()
+		};
+
+		// Test with USDT token
+		assert_eq!(req_allowance(StableCoin::USDT, "0", false), "11.112192");
+		assert_eq!(req_allowance(StableCoin::USDT, "0", true), "11.000000");
+		// sufficient allowance
+		assert_eq!(req_allowance(StableCoin::USDT, "11112192", false), "0.000000");
+
 		// Test with DAI token
+		assert_eq!(req_allowance(StableCoin::DAI, "0", false), "11.112192000000000000");
+		assert_eq!(req_allowance(StableCoin::DAI, "0", true), "11.000000000000000000");
+	}
+
+	#[test]
+	fn validates_amount() {
+		let usdt = |v| Amount::parse(v, StableCoin::USDT, ChainName::Ethereum).unwrap();
+		let (balance, est_fee) = (usdt("10"), usdt("0.5"));
+
+		assert!(validate_amount(&usdt("9.5"), &balance, &est_fee, false).is_ok());
+		assert!(validate_amount(&usdt("10"), &balance, &est_fee, true).is_ok());
+		let err = validate_amount(&usdt("9.6"), &balance, &est_fee, false).unwrap_err();
+		assert!(matches!(err.downcast_ref(), Some(UfiError::InsufficientBalance)));
+		let err = validate_amount(&usdt("0"), &balance, &est_fee, true).unwrap_err();
+		assert!(matches!(err.downcast_ref(), Some(UfiError::ZeroAmount)));
+		let usdc = Amount::parse("1", StableCoin::USDC, ChainName::Ethereum).unwrap();
+		let err = validate_amount(&usdc, &balance, &est_fee, true).unwrap_err();
+		assert!(matches!(err.downcast_ref(), Some(UfiError::CoinMismatch(..))));
+
+		assert_eq!(
+			fmt_value("10500000", StableCoin::USDT, ChainName::Ethereum).unwrap(),
+			"10.500000"
+		);
 		assert_eq!(
-			update_req_allowance("1", StableCoin::DAI, "0", "0.112192", false).unwrap(),
-			"11.112192000000000000"
+			sanitize_and_parse_amount("1.5", StableCoin::USDT, ChainName::Ethereum).unwrap(),
+			U256::from(1_500_000)
 		);
+	}
+
+	#[test]
+	fn applies_fee_policy() {
+		let payload = PreOcpPayload { coin: StableCoin::USDC, chain: ChainName::Ethereum };
+		let params = PreOcpValuesNcwParams {
+			allowance: U256::MAX.to_string(),
+			balance: "100".to_owned(),
+			gas_price: 10_000_000_000,
+			gas_token_price: "3000".to_owned(),
+			coin_price: "1".to_owned(),
+			l1_fee: None,
+		};
+		let est_fee = |policy: &FeePolicy| {
+			compute_est_fee_ncw(payload, "10", &params, true, false, policy).unwrap().2
+		};
+
+		// network fee: 120_000 * 10 gwei * $3000 = $3.6
+		let policy = FeePolicy { multiplier_bps: 11_000, ..Default::default() };
+		assert_eq!(est_fee(&policy), "3.960000");
+		let policy = FeePolicy { surcharge: "0.25".to_owned(), ..policy };
+		assert_eq!(est_fee(&policy), "4.210000");
+		let policy = FeePolicy { min_fee: "5".to_owned(), ..policy };
+		assert_eq!(est_fee(&policy), "5.000000");
+
+		let policy = FeePolicy { allowance_buffer: "2.5".to_owned(), ..Default::default() };
+		let usdc = |v| Amount::parse(v, StableCoin::USDC, ChainName::Ethereum).unwrap();
 		assert_eq!(
-			update_req_allowance("1", StableCoin::DAI, "0", "0.112192", true).unwrap(),
-			"11.000000000000000000"
+			update_req_allowance(&usdc("1"), "0", &usdc("0.5"), false, &policy)
+				.unwrap()
+				.to_string(),
+			"4.000000"
 		);
 	}
 }
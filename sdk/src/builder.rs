@@ -0,0 +1,273 @@
+use crate::{
+	Sdk,
+	errors::{SdkError, SdkResult},
+	retry::RetryPolicy,
+};
+use reqwest::{
+	Client as reqwestClient, Proxy, Url,
+	header::{HeaderMap, HeaderName, HeaderValue},
+};
+use std::time::Duration;
+
+/// Builder for [`Sdk`] to configure the underlying HTTP client.
+///
+/// ## Example
+/// ```no_run
+/// use std::time::Duration;
+/// use unifi_sdk_rs::Sdk;
+///
+/// let sdk = Sdk::builder()
+///     .api_base_url("https://unifi-api-4a5e.onrender.com/")
+///     .api_key("YOUR_API_KEY")
+///     .timeout(Duration::from_secs(20))
+///     .user_agent("my-app/1.0")
+///     .build()
+///     .unwrap();
+/// ```
+///
+/// NOTE: If a ready-made `reqwest::Client` is set via [`SdkBuilder::client`], then the client
+/// settings (timeouts, proxy, user agent, default headers) can't be set as they would be ignored.
+#[derive(Debug, Default)]
+pub struct SdkBuilder {
+	api_base_url: Option<String>,
+	api_key: Option<String>,
+	client: Option<reqwestClient>,
+	retry_policy: Option<RetryPolicy>,
+	timeout: Option<Duration>,
+	connect_timeout: Option<Duration>,
+	proxy: Option<Proxy>,
+	user_agent: Option<String>,
+	default_headers: HeaderMap,
+	/// Invalid header name/value set via [`SdkBuilder::header`], reported on `build`.
+	invalid_header: Option<String>,
+}
+
+impl SdkBuilder {
+	/// Default total request timeout.
+	pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+	/// Default connect timeout.
+	pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+	/// Default `User-Agent` header.
+	pub const DEFAULT_USER_AGENT: &str = concat!("unifi-sdk-rs/", env!("CARGO_PKG_VERSION"));
+
+	pub fn api_base_url(mut self, api_base_url: &str) -> Self {
+		self.api_base_url = Some(api_base_url.to_owned());
+		self
+	}
+
+	pub fn api_key(mut self, api_key: &str) -> Self {
+		self.api_key = Some(api_key.to_owned());
+		self
+	}
+
+	/// Use a ready-made `reqwest::Client` instead of building one.
+	pub fn client(mut self, client: reqwestClient) -> Self {
+		self.client = Some(client);
+		self
+	}
+
+	/// Retry policy for transient failures. Default: [`RetryPolicy::default`].
+	pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+		self.retry_policy = Some(retry_policy);
+		self
+	}
+
+	/// Total request timeout (connect + send + read). Default: [`Self::DEFAULT_TIMEOUT`].
+	pub fn timeout(mut self, timeout: Duration) -> Self {
+		self.timeout = Some(timeout);
+		self
+	}
+
+	/// Connect timeout. Default: [`Self::DEFAULT_CONNECT_TIMEOUT`].
+	pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+		self.connect_timeout = Some(connect_timeout);
+		self
+	}
+
+	pub fn proxy(mut self, proxy: Proxy) -> Self {
+		self.proxy = Some(proxy);
+		self
+	}
+
+	/// `User-Agent` header. Default: [`Self::DEFAULT_USER_AGENT`].
+	pub fn user_agent(mut self, user_agent: &str) -> Self {
+		self.user_agent = Some(user_agent.to_owned());
+		self
+	}
+
+	/// Extra headers sent with every request.
+	pub fn default_headers(mut self, headers: HeaderMap) -> Self {
+		self.default_headers.extend(headers);
+		self
+	}
+
+	/// Extra header sent with every request.
+	pub fn header(mut self, name: &str, value: &str) -> Self {
+		match (HeaderName::try_from(name), HeaderValue::try_from(value)) {
+			(Ok(name), Ok(value)) => {
+				self.default_headers.insert(name, value);
+			},
+			_ => self.invalid_header = Some(name.to_owned()),
+		}
+		self
+	}
+
+	pub fn build(self) -> SdkResult<Sdk> {
+		let Self {
+			api_base_url,
+			api_key,
+			client,
+			retry_policy,
+			timeout,
+			connect_timeout,
+			proxy,
+			user_agent,
+			default_headers,
+			invalid_header,
+		} = self;
+
+		let api_base_url =
+			api_base_url.ok_or_else(|| SdkError::Config("`api_base_url` is not set".to_owned()))?;
+		let api_base_url = validate_api_base_url(&api_base_url)?;
+		let api_key = api_key.ok_or_else(|| SdkError::Config("`api_key` is not set".to_owned()))?;
+		if let Some(name) = invalid_header {
+			return Err(SdkError::Config(format!("Invalid header: {}", name)));
+		}
+
+		let has_client_settings = timeout.is_some() ||
+			connect_timeout.is_some() ||
+			proxy.is_some() ||
+			user_agent.is_some() ||
+			!default_headers.is_empty();
+
+		let client = match client {
+			Some(_) if has_client_settings =>
+				return Err(SdkError::Config(
+					"Client settings can't be set along with a custom `reqwest::Client`".to_owned(),
+				)),
+			Some(client) => client,
+			None => {
+				let mut builder = reqwestClient::builder()
+					.timeout(timeout.unwrap_or(Self::DEFAULT_TIMEOUT))
+					.connect_timeout(connect_timeout.unwrap_or(Self::DEFAULT_CONNECT_TIMEOUT))
+					.user_agent(user_agent.as_deref().unwrap_or(Self::DEFAULT_USER_AGENT))
+					.default_headers(default_headers);
+				if let Some(proxy) = proxy {
+					builder = builder.proxy(proxy);
+				}
+				builder.build().map_err(|e| SdkError::Config(e.to_string()))?
+			},
+		};
+
+		Ok(Sdk { client, api_base_url, api_key, retry_policy: retry_policy.unwrap_or_default() })
+	}
+
+	/// `reqwest::Client` with the default settings (timeouts, user agent) i.e. as built by
+	/// [`Self::build`] when no client settings are set.
+	///
+	/// ## Panics
+	/// If the TLS backend can't be initialized, same as `reqwest::Client::new`.
+	pub(crate) fn default_client() -> reqwestClient {
+		Self::default()
+			.api_base_url("http://localhost")
+			.api_key("")
+			.build()
+			.expect("Failed to build the default `reqwest::Client`")
+			.client
+	}
+}
+
+/// Validate the API base URL & normalize it i.e. without trailing slashes.
+///
+/// ## Example
+/// input: "https://unifi-api-4a5e.onrender.com/" \
+/// output: "https://unifi-api-4a5e.onrender.com"
+pub(crate) fn validate_api_base_url(api_base_url: &str) -> SdkResult<String> {
+	let api_base_url = normalize_api_base_url(api_base_url);
+	let url = Url::parse(&api_base_url).map_err(|e| {
+		SdkError::Config(format!("Invalid `api_base_url`: {}: {}", api_base_url, e))
+	})?;
+
+	if !matches!(url.scheme(), "http" | "https") {
+		return Err(SdkError::Config(format!(
+			"Invalid `api_base_url` scheme: {}. Allowed: \"http\", \"https\".",
+			url.scheme()
+		)));
+	}
+	if !url.has_host() {
+		return Err(SdkError::Config(format!("Missing host in `api_base_url`: {}", api_base_url)));
+	}
+	if url.query().is_some() || url.fragment().is_some() {
+		return Err(SdkError::Config(format!(
+			"Query/fragment not allowed in `api_base_url`: {}",
+			api_base_url
+		)));
+	}
+
+	Ok(api_base_url)
+}
+
+pub(crate) fn normalize_api_base_url(api_base_url: &str) -> String {
+	api_base_url.trim().trim_end_matches('/').to_owned()
+}
+
+/// Test
+/// ```sh
+/// RUSTFLAGS="-Awarnings" cargo t -p unifi-sdk-rs -- builder::tests --show-output
+/// ```
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn normalizes_api_base_url() {
+		assert_eq!(
+			validate_api_base_url(" https://api.unifi.io/ ").unwrap(),
+			"https://api.unifi.io"
+		);
+		assert_eq!(
+			validate_api_base_url("http://localhost:8080//").unwrap(),
+			"http://localhost:8080"
+		);
+		assert_eq!(
+			validate_api_base_url("https://api.unifi.io/v1/").unwrap(),
+			"https://api.unifi.io/v1"
+		);
+	}
+
+	#[test]
+	fn rejects_invalid_api_base_url() {
+		assert!(validate_api_base_url("").is_err());
+		assert!(validate_api_base_url("api.unifi.io").is_err());
+		assert!(validate_api_base_url("ftp://api.unifi.io").is_err());
+		assert!(validate_api_base_url("https://api.unifi.io/?x=1").is_err());
+	}
+
+	#[test]
+	fn build_checks_settings() {
+		let sdk = Sdk::builder().api_base_url("https://api.unifi.io/").api_key("key").build();
+		assert_eq!(sdk.unwrap().api_base_url, "https://api.unifi.io");
+
+		assert!(matches!(
+			Sdk::builder().api_base_url("https://api.unifi.io").build(),
+			Err(SdkError::Config(_))
+		));
+		assert!(matches!(
+			Sdk::builder()
+				.api_base_url("https://api.unifi.io")
+				.api_key("key")
+				.header("X-Bad\n", "value")
+				.build(),
+			Err(SdkError::Config(_))
+		));
+		assert!(matches!(
+			Sdk::builder()
+				.api_base_url("https://api.unifi.io")
+				.api_key("key")
+				.client(reqwestClient::new())
+				.timeout(Duration::from_secs(1))
+				.build(),
+			Err(SdkError::Config(_))
+		));
+	}
+}
@@ -0,0 +1,90 @@
+use super::Sdk;
+use crate::{
+	errors::{SdkError, SdkResult},
+	types::{ApiHandler, fill_path},
+};
+use unifi_sdk_primitives::{
+	types::{Contact, ContactPayload},
+	utils::sanitize_address,
+};
+
+impl Sdk {
+	/// Add one or more contacts to the user's contact book.
+	///
+	/// ## Returns
+	/// The added contacts with their UniFi generated `uid`.
+	pub async fn add_user_contacts(
+		&self,
+		user_id: &str,
+		contacts: &[ContactPayload],
+	) -> SdkResult<Vec<Contact>> {
+		for contact in contacts {
+			if !sanitize_address(&contact.address) {
+				return Err(SdkError::InvalidInput(format!(
+					"Invalid address: {} for contact: {}",
+					contact.address, contact.name
+				)));
+			}
+		}
+
+		let (handler, path) = fill_path!(ApiHandler::AddUserContacts, user_id)?;
+		let req = self.request(handler, &path).json(&contacts);
+
+		self.execute::<Vec<Contact>>(handler, req).await
+	}
+
+	/// Delete a contact (by `uid`) from the user's contact book.
+	pub async fn del_user_contact(&self, user_id: &str, uid: &str) -> SdkResult<()> {
+		let (handler, path) = fill_path!(ApiHandler::DelUserContact, user_id, uid)?;
+		let req = self.request(handler, &path);
+
+		self.execute::<()>(handler, req).await
+	}
+
+	/// Update a contact (by `uid`) in the user's contact book.
+	pub async fn update_user_contact(
+		&self,
+		user_id: &str,
+		uid: &str,
+		contact: &ContactPayload,
+	) -> SdkResult<()> {
+		if !sanitize_address(&contact.address) {
+			return Err(SdkError::InvalidInput(format!("Invalid address: {}", contact.address)));
+		}
+
+		let (handler, path) = fill_path!(ApiHandler::UpdateUserContact, user_id, uid)?;
+		let req = self.request(handler, &path).json(contact);
+
+		self.execute::<()>(handler, req).await
+	}
+
+	/// Get all the contacts of a user.
+	pub async fn get_user_contacts(&self, user_id: &str) -> SdkResult<Vec<Contact>> {
+		let (handler, path) = fill_path!(ApiHandler::GetUserContacts, user_id)?;
+		let req = self.request(handler, &path);
+
+		self.execute::<Vec<Contact>>(handler, req).await
+	}
+
+	/// Get a user's contact by `uid`.
+	pub async fn get_user_contact_by_uid(&self, user_id: &str, uid: &str) -> SdkResult<Contact> {
+		let (handler, path) = fill_path!(ApiHandler::GetUserContactByUid, user_id, uid)?;
+		let req = self.request(handler, &path);
+
+		self.execute::<Contact>(handler, req).await
+	}
+
+	/// Get a user's contacts matching the `name`.
+	///
+	/// NOTE: Multiple contacts can share the same name. Hence, a list is returned.
+	pub async fn get_user_contacts_by_name(
+		&self,
+		user_id: &str,
+		name: &str,
+	) -> SdkResult<Vec<Contact>> {
+		let (handler, path) = fill_path!(ApiHandler::GetUserContactsByName, user_id, name)?;
+		let req = self.request(handler, &path);
+
+		self.execute::<Vec<Contact>>(handler, req).await
+	}
+}
@@ -1,4 +1,8 @@
+use crate::http_error_message;
+use serde::Deserialize;
+use std::time::Duration;
 use thiserror::Error as ThisError;
+use unifi_sdk_primitives::types::{OcPayReceipt, OcPayReceiptStatus};
 
 #[allow(dead_code)]
 #[derive(ThisError, Debug)]
@@ -7,10 +11,263 @@ pub enum OmniPayError {
 		"API Server is Offline. \nPlease retry later or contact support if the problem persists."
 	)]
 	RequestToAPIServerFailed,
-	#[error("Not enough params provided to fill all placeholders.")]
-	LessParamsForApiPath,
-	#[error("Extra params provided to fill all placeholders.")]
-	MoreParamsForApiPath,
+	#[error("No param provided to fill the placeholder: {{{0}}}.")]
+	MissingParamForApiPath(String),
+	#[error("No placeholder found for the param: {0}.")]
+	UnknownParamForApiPath(String),
+	#[error("Param provided more than once: {0}.")]
+	DuplicateParamForApiPath(String),
 	#[error("Unclosed placeholder found in template.")]
 	UnclosedPlaceholderInApiPathTemplate,
 }
+
+/// Result returned by every [`Sdk`](crate::Sdk) method.
+pub type SdkResult<T> = Result<T, SdkError>;
+
+/// Error body sent by UniFi API for a non-2xx response.
+///
+/// NOTE: If the body isn't JSON, the raw text is kept as `message`.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct ApiErrorBody {
+	#[serde(default)]
+	pub status: String,
+	#[serde(alias = "error", alias = "data")]
+	pub message: String,
+}
+
+impl ApiErrorBody {
+	pub(crate) fn parse(text: &str) -> Self {
+		serde_json::from_str::<Self>(text)
+			.unwrap_or_else(|_| Self { status: "error".to_owned(), message: text.to_owned() })
+	}
+
+	/// Receipt ID mentioned in the message. E.g. "Duplicate payment. Receipt ID: 1ra0e4b3cd"
+	pub(crate) fn receipt_id(&self) -> Option<String> {
+		let (_, id) = self.message.split_once("Receipt ID:")?;
+		let id = id.trim().trim_end_matches('.');
+		(!id.is_empty()).then(|| id.to_owned())
+	}
+}
+
+/// Typed error for all the [`Sdk`](crate::Sdk) methods.
+///
+/// HTTP failures are split by status code (as per `http_error_message!`) so that callers can
+/// `match` on them, e.g. re-login on `Unauthorized`, back off on `RateLimited`.
+#[derive(ThisError, Debug)]
+pub enum SdkError {
+	#[error("{}: {}", http_error_message!(401), .body.message)]
+	Unauthorized { body: ApiErrorBody },
+	#[error("{}: {}", http_error_message!(403), .body.message)]
+	Forbidden { body: ApiErrorBody },
+	#[error("{}: {}", http_error_message!(404), .body.message)]
+	NotFound { body: ApiErrorBody },
+	#[error("{}: {}", http_error_message!(409), .body.message)]
+	Conflict { body: ApiErrorBody },
+	/// `retry_after` is parsed from the `Retry-After` header (if sent by the server).
+	#[error("{}: {}", http_error_message!(429), .body.message)]
+	RateLimited { retry_after: Option<Duration>, body: ApiErrorBody },
+	/// Payment with the same idempotency key was already submitted (`409`).
+	///
+	/// `receipt_id` is of the already submitted payment, parsed from the error message (if sent
+	/// by the server). NOTE: It might be of an earlier attempt of the same call, whose response was
+	/// lost (e.g. timed out) & then retried. So, track that receipt instead of paying again.
+	#[error("Duplicate payment (idempotency key: {idempotency_key}): {}", .body.message)]
+	DuplicatePayment { idempotency_key: String, receipt_id: Option<String>, body: ApiErrorBody },
+	/// Any other 4xx status.
+	#[error("{}: {}", http_error_message!(*.status), .body.message)]
+	Client { status: u16, body: ApiErrorBody },
+	/// Any 5xx status.
+	#[error("{}: {}", http_error_message!(*.status), .body.message)]
+	Server { status: u16, body: ApiErrorBody },
+	/// Any other non-2xx status i.e. 1xx or 3xx (e.g. a redirect not followed).
+	#[error("Unexpected response status {status}: {}", .body.message)]
+	UnexpectedStatus { status: u16, body: ApiErrorBody },
+	/// Successful response, but the body couldn't be parsed into the expected type.
+	#[error("Failed to parse response: {0}")]
+	Decode(#[source] reqwest::Error),
+	/// Request couldn't be sent or the response couldn't be read.
+	#[error("Request failed: {0}")]
+	Transport(#[source] reqwest::Error),
+	/// Request couldn't be built or the API server is unreachable.
+	#[error(transparent)]
+	Request(#[from] OmniPayError),
+	/// Input rejected locally before sending the request.
+	#[error("Invalid input: {0}")]
+	InvalidInput(String),
+	/// Payment found as invalid by UniFi (insufficient balance, etc..).
+	#[error("Payment failed. Receipt ID: {}", .0.id)]
+	PaymentFailed(Box<OcPayReceipt>),
+	/// Payment not added to canonical chain. Hence, payer needs to retry the payment.
+	#[error("Payment reorged. Please retry the payment. Receipt ID: {}", .0.id)]
+	PaymentReorged(Box<OcPayReceipt>),
+	/// Receipt didn't reach the expected status in time.
+	#[error("Timed out waiting for receipt: {receipt_id} (last status: {last_status})")]
+	WaitTimeout { receipt_id: String, last_status: OcPayReceiptStatus },
+	/// Wait cancelled by the caller.
+	#[error("Cancelled")]
+	Cancelled,
+	/// Invalid [`SdkBuilder`](crate::builder::SdkBuilder) settings.
+	#[error("Invalid SDK config: {0}")]
+	Config(String),
+}
+
+impl SdkError {
+	/// Build the error from a non-2xx response's status, headers & body text.
+	pub(crate) fn from_status(
+		status: u16,
+		headers: &reqwest::header::HeaderMap,
+		text: &str,
+	) -> Self {
+		let body = ApiErrorBody::parse(text);
+		match status {
+			401 => Self::Unauthorized { body },
+			403 => Self::Forbidden { body },
+			404 => Self::NotFound { body },
+			409 => Self::Conflict { body },
+			429 => Self::RateLimited { retry_after: parse_retry_after(headers), body },
+			400..=499 => Self::Client { status, body },
+			500..=599 => Self::Server { status, body },
+			_ => Self::UnexpectedStatus { status, body },
+		}
+	}
+
+	/// HTTP status code, if the error came from an API response.
+	pub fn status(&self) -> Option<u16> {
+		match self {
+			Self::Unauthorized { .. } => Some(401),
+			Self::Forbidden { .. } => Some(403),
+			Self::NotFound { .. } => Some(404),
+			Self::Conflict { .. } | Self::DuplicatePayment { .. } => Some(409),
+			Self::RateLimited { .. } => Some(429),
+			Self::Client { status, .. } |
+			Self::Server { status, .. } |
+			Self::UnexpectedStatus { status, .. } => Some(*status),
+			Self::Decode(_) |
+			Self::Transport(_) |
+			Self::Request(_) |
+			Self::InvalidInput(_) |
+			Self::PaymentFailed(_) |
+			Self::PaymentReorged(_) |
+			Self::WaitTimeout { .. } |
+			Self::Cancelled |
+			Self::Config(_) => None,
+		}
+	}
+
+	/// Is the failure transient i.e. worth retrying the same request?
+	pub fn is_retryable(&self) -> bool {
+		match self {
+			Self::RateLimited { .. } => true,
+			// 501: Not Implemented won't change on retry.
+			Self::Server { status, .. } => *status != 501,
+			Self::Transport(err) => err.is_timeout() || err.is_connect(),
+			Self::Request(OmniPayError::RequestToAPIServerFailed) => true,
+			_ => false,
+		}
+	}
+
+	/// Error body sent by the server, if any.
+	pub fn body(&self) -> Option<&ApiErrorBody> {
+		match self {
+			Self::Unauthorized { body } |
+			Self::Forbidden { body } |
+			Self::NotFound { body } |
+			Self::Conflict { body } |
+			Self::DuplicatePayment { body, .. } |
+			Self::RateLimited { body, .. } |
+			Self::Client { body, .. } |
+			Self::Server { body, .. } |
+			Self::UnexpectedStatus { body, .. } => Some(body),
+			Self::Decode(_) |
+			Self::Transport(_) |
+			Self::Request(_) |
+			Self::InvalidInput(_) |
+			Self::PaymentFailed(_) |
+			Self::PaymentReorged(_) |
+			Self::WaitTimeout { .. } |
+			Self::Cancelled |
+			Self::Config(_) => None,
+		}
+	}
+}
+
+/// Parse `Retry-After` header, either in delay-seconds or HTTP-date format.
+fn parse_retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+	let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.trim();
+	if let Ok(secs) = value.parse::<u64>() {
+		return Some(Duration::from_secs(secs));
+	}
+
+	let at = httpdate::parse_http_date(value).ok()?;
+	Some(at.duration_since(std::time::SystemTime::now()).unwrap_or_default())
+}
+
+/// Test
+/// ```sh
+/// RUSTFLAGS="-Awarnings" cargo t -p unifi-sdk-rs -- errors::tests --show-output
+/// ```
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+
+	#[test]
+	fn maps_status_to_variant() {
+		let headers = HeaderMap::new();
+		let body = r#"{"status":"error","message":"Invalid API key"}"#;
+
+		let err = SdkError::from_status(401, &headers, body);
+		assert!(
+			matches!(&err, SdkError::Unauthorized { body } if body.message == "Invalid API key")
+		);
+		assert_eq!(err.status(), Some(401));
+
+		assert!(matches!(SdkError::from_status(409, &headers, body), SdkError::Conflict { .. }));
+		assert!(matches!(
+			SdkError::from_status(503, &headers, body),
+			SdkError::Server { status: 503, .. }
+		));
+		assert!(matches!(
+			SdkError::from_status(422, &headers, body),
+			SdkError::Client { status: 422, .. }
+		));
+		let err = SdkError::from_status(302, &headers, body);
+		assert!(matches!(err, SdkError::UnexpectedStatus { status: 302, .. }));
+		assert_eq!(err.status(), Some(302));
+		assert!(!err.is_retryable());
+		assert!(matches!(
+			SdkError::from_status(101, &headers, body),
+			SdkError::UnexpectedStatus { status: 101, .. }
+		));
+	}
+
+	#[test]
+	fn parses_receipt_id() {
+		let body = ApiErrorBody::parse("Duplicate payment. Receipt ID: 1ra0e4b3cd");
+		assert_eq!(body.receipt_id().as_deref(), Some("1ra0e4b3cd"));
+		assert_eq!(ApiErrorBody::parse("Duplicate payment.").receipt_id(), None);
+		assert_eq!(ApiErrorBody::parse("Receipt ID: ").receipt_id(), None);
+	}
+
+	#[test]
+	fn keeps_raw_text_body() {
+		let err = SdkError::from_status(404, &HeaderMap::new(), "User not found");
+		assert_eq!(err.body().unwrap().message, "User not found");
+	}
+
+	#[test]
+	fn parses_retry_after() {
+		let mut headers = HeaderMap::new();
+		headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+		assert!(matches!(
+			SdkError::from_status(429, &headers, ""),
+			SdkError::RateLimited { retry_after: Some(d), .. } if d == Duration::from_secs(7)
+		));
+
+		headers.insert(RETRY_AFTER, HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"));
+		assert!(matches!(
+			SdkError::from_status(429, &headers, ""),
+			SdkError::RateLimited { retry_after: Some(Duration::ZERO), .. }
+		));
+	}
+}
@@ -1,19 +1,17 @@
-use crate::{Sdk, types::ApiHandler};
+use crate::{Sdk, errors::SdkResult, types::ApiHandler};
 
 impl Sdk {
-	pub async fn healthz(&self) -> eyre::Result<String> {
+	pub async fn healthz(&self) -> SdkResult<String> {
 		let handler = ApiHandler::Healthz;
-		let url = format!("{}{}", self.api_base_url, handler.path());
-		let resp = self.with_auth(self.client.get(url)).send().await;
+		let req = self.request(handler, handler.path());
 
-		Sdk::process_response::<String>(resp).await
+		self.execute::<String>(handler, req).await
 	}
 
-	pub async fn health_check(&self) -> eyre::Result<String> {
+	pub async fn health_check(&self) -> SdkResult<String> {
 		let handler = ApiHandler::HealthCheck;
-		let url = format!("{}{}", self.api_base_url, handler.path());
-		let resp = self.with_auth(self.client.get(url)).send().await;
+		let req = self.request(handler, handler.path());
 
-		Sdk::process_response::<String>(resp).await
+		self.execute::<String>(handler, req).await
 	}
 }
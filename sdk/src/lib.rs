@@ -1,11 +1,19 @@
-use errors::OmniPayError;
-use reqwest::{Client as reqwestClient, Error as reqwestError, Response as reqwestResponse};
+use builder::{SdkBuilder, normalize_api_base_url};
+use errors::{OmniPayError, SdkError, SdkResult};
+use reqwest::{
+	Client as reqwestClient, Error as reqwestError, RequestBuilder, Response as reqwestResponse,
+};
+use retry::RetryPolicy;
+use types::ApiHandler;
 use unifi_sdk_primitives::ApiResponse;
 
+pub mod builder;
+pub mod contacts;
 pub mod errors;
 pub mod health;
 pub mod pay;
 pub mod profile;
+pub mod retry;
 pub mod types;
 pub mod wallet;
 
@@ -36,24 +44,71 @@ pub struct Sdk {
 	pub client: reqwestClient,
 	pub api_base_url: String,
 	pub api_key: String,
+	/// Retry policy for transient failures. Default: [`RetryPolicy::default`].
+	pub retry_policy: RetryPolicy,
 }
 
 impl Sdk {
+	/// Shortcut for an SDK with the [`SdkBuilder`] defaults e.g. timeouts
+	/// ([`SdkBuilder::DEFAULT_TIMEOUT`], [`SdkBuilder::DEFAULT_CONNECT_TIMEOUT`]).
+	///
+	/// NOTE: `api_base_url` is only normalized (not validated). Use [`Sdk::builder`] to validate
+	/// it and to set timeouts, proxy, user agent, default headers or a custom client.
 	pub fn new(api_base_url: &str, api_key: &str) -> Self {
 		Self {
-			client: reqwestClient::new(),
-			api_base_url: api_base_url.to_owned(),
+			client: SdkBuilder::default_client(),
+			api_base_url: normalize_api_base_url(api_base_url),
 			api_key: api_key.to_owned(),
+			retry_policy: RetryPolicy::default(),
 		}
 	}
 
+	pub fn builder() -> SdkBuilder {
+		SdkBuilder::default()
+	}
+
 	pub(crate) fn with_auth(&self, req: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
 		req.header("Authorization", format!("Bearer {}", self.api_key))
 	}
 
+	/// Build a request for the handler's method to the given (filled) path.
+	pub(crate) fn request(&self, handler: ApiHandler, path: &str) -> RequestBuilder {
+		self.client.request(handler.method(), format!("{}{}", self.api_base_url, path))
+	}
+
+	/// Send the request (with auth) & process the response, retrying transient failures as per
+	/// the [`RetryPolicy`].
+	pub(crate) async fn execute<T>(&self, handler: ApiHandler, req: RequestBuilder) -> SdkResult<T>
+	where
+		T: serde::de::DeserializeOwned + Default + std::fmt::Debug + 'static,
+	{
+		let mut request = self.with_auth(req).build().map_err(SdkError::Transport)?;
+		let can_retry = self.retry_policy.allows(handler, &request);
+
+		let mut attempt = 1;
+		loop {
+			// NOTE: Bodies are always JSON bytes (never streamed). So, cloning never fails.
+			let retry_request = if can_retry { request.try_clone() } else { None };
+
+			let err = match Sdk::process_response::<T>(self.client.execute(request).await).await {
+				Ok(data) => return Ok(data),
+				Err(err) => err,
+			};
+			let (Some(retry_request), Some(delay)) =
+				(retry_request, self.retry_policy.next_delay(attempt, &err))
+			else {
+				return Err(err);
+			};
+
+			tokio::time::sleep(delay).await;
+			request = retry_request;
+			attempt += 1;
+		}
+	}
+
 	pub(crate) async fn process_response<T>(
 		resp: core::result::Result<reqwestResponse, reqwestError>,
-	) -> eyre::Result<T>
+	) -> SdkResult<T>
 	where
 		T: serde::de::DeserializeOwned + Default + std::fmt::Debug + 'static,
 	{
@@ -70,16 +125,15 @@ impl Sdk {
 					// Deserialize JSON response for `T`
 					match resp.json::<ApiResponse<T>>().await {
 						Ok(resp) => return Ok(resp.data),
-						Err(err) => return Err(eyre::eyre!("Failed to parse response: {}", err)),
+						Err(err) => return Err(SdkError::Decode(err)),
 					}
 				}
 
 				// Handle error response
+				let headers = resp.headers().clone();
 				let error_text =
 					resp.text().await.unwrap_or_else(|_| "Failed to read error body.".to_string());
-				Err(eyre::eyre!("{}", error_text))
-				// return Err(eyre::eyre!("{}: {}", http_error_message!(status.as_u16()),
-				// error_text));
+				Err(SdkError::from_status(status.as_u16(), &headers, &error_text))
 			},
 			Err(err) => {
 				#[cfg(not(target_arch = "wasm32"))]
@@ -96,7 +150,7 @@ impl Sdk {
 				}
 
 				// General error handling for other `reqwest` errors
-				Err(eyre::eyre!("Request failed: {}", err))
+				Err(SdkError::Transport(err))
 			},
 		}
 	}
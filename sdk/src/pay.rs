@@ -1,7 +1,14 @@
-use crate::{Sdk, types::ApiHandler};
+use crate::{
+	Sdk,
+	errors::{SdkError, SdkResult},
+	retry::IDEMPOTENCY_KEY_HEADER,
+	types::{ApiHandler, WaitForReceiptOpts, fill_path},
+};
+use futures::{Stream, StreamExt, TryStreamExt, stream};
+use reqwest::RequestBuilder;
 use unifi_sdk_primitives::types::{
-	ChainName, OcPayHistory, OcPayReceipt, PayHistoryFilterParams, PayOnchainPayload,
-	PreOcpPayload, PreOcpValues, StableCoin,
+	ChainName, OcPayHistory, OcPayReceipt, OcPayReceiptStatus, PayHistoryFilterParams,
+	PayOnchainPayload, PreOcpPayload, PreOcpValues, StableCoin,
 };
 
 impl Sdk {
@@ -10,18 +17,13 @@ impl Sdk {
 		&self,
 		user_id: &str,
 		payload: PreOcpPayload,
-	) -> eyre::Result<String> {
+	) -> SdkResult<String> {
 		let PreOcpPayload { coin, chain } = payload;
-		let handler = ApiHandler::FetchPreOcpNetOnchainBalance;
-		let path = handler.fill_path_ordered(&[
-			user_id.to_owned(),
-			chain.to_string(),
-			coin.to_string(),
-		])?;
-		let url = format!("{}{}", self.api_base_url, path);
-		let resp = self.with_auth(self.client.get(url)).send().await;
-
-		Sdk::process_response::<String>(resp).await
+		let (handler, path) =
+			fill_path!(ApiHandler::FetchPreOcpNetOnchainBalance, user_id, chain, coin)?;
+		let req = self.request(handler, &path);
+
+		self.execute::<String>(handler, req).await
 	}
 
 	/// Fetch the pre-ocp est. total fee for the given coin & chain in case of on-chain
@@ -30,18 +32,13 @@ impl Sdk {
 		&self,
 		user_id: &str,
 		payload: PreOcpPayload,
-	) -> eyre::Result<String> {
+	) -> SdkResult<String> {
 		let PreOcpPayload { coin, chain } = payload;
-		let handler = ApiHandler::FetchPreOcpTotalEstFees;
-		let path = handler.fill_path_ordered(&[
-			user_id.to_owned(),
-			chain.to_string(),
-			coin.to_string(),
-		])?;
-		let url = format!("{}{}", self.api_base_url, path);
-		let resp = self.with_auth(self.client.get(url)).send().await;
-
-		Sdk::process_response::<String>(resp).await
+		let (handler, path) =
+			fill_path!(ApiHandler::FetchPreOcpTotalEstFees, user_id, chain, coin)?;
+		let req = self.request(handler, &path);
+
+		self.execute::<String>(handler, req).await
 	}
 
 	/// Fetch the pre-ocp balance & est. total fee for the given coin & chain in case of on-chain
@@ -50,18 +47,13 @@ impl Sdk {
 		&self,
 		user_id: &str,
 		payload: PreOcpPayload,
-	) -> eyre::Result<PreOcpValues> {
+	) -> SdkResult<PreOcpValues> {
 		let PreOcpPayload { coin, chain } = payload;
-		let handler = ApiHandler::FetchPreOcpBalanceAndEstFees;
-		let path = handler.fill_path_ordered(&[
-			user_id.to_owned(),
-			chain.to_string(),
-			coin.to_string(),
-		])?;
-		let url = format!("{}{}", self.api_base_url, path);
-		let resp = self.with_auth(self.client.get(url)).send().await;
-
-		Sdk::process_response::<PreOcpValues>(resp).await
+		let (handler, path) =
+			fill_path!(ApiHandler::FetchPreOcpBalanceAndEstFees, user_id, chain, coin)?;
+		let req = self.request(handler, &path);
+
+		self.execute::<PreOcpValues>(handler, req).await
 	}
 
 	/// Request Airdrop on testnet
@@ -70,106 +62,286 @@ impl Sdk {
 		user_id: &str,
 		coin: StableCoin,
 		chain: ChainName,
-	) -> eyre::Result<()> {
-		let handler = ApiHandler::RequestFaucet;
-		let path = handler.fill_path_ordered(&[
-			user_id.to_owned(),
-			coin.to_string(),
-			chain.to_string(),
-		])?;
-		let url = format!("{}{}", self.api_base_url, path);
-		let resp = self.with_auth(self.client.post(url)).send().await;
-
-		Sdk::process_response::<()>(resp).await
+	) -> SdkResult<()> {
+		let (handler, path) = fill_path!(ApiHandler::RequestFaucet, user_id, coin, chain)?;
+		let req = self.request(handler, &path);
+
+		self.execute::<()>(handler, req).await
 	}
 
 	/// Pay onchain
+	///
+	/// NOTE: Not retried on failure as the payment might have gone through. Use
+	/// [`Sdk::pay_onchain_with_idempotency_key`] to make it safe to retry.
 	pub async fn pay_onchain(
 		&self,
 		user_id: &str,
 		is_fee_incl: bool,
 		payload: PayOnchainPayload,
-	) -> eyre::Result<String> {
-		let handler = ApiHandler::PayOnchain;
-		let path = handler.fill_path_ordered(&[user_id.to_string(), is_fee_incl.to_string()])?;
-		let url = format!("{}{}", self.api_base_url, path);
-		let resp = self.with_auth(self.client.post(url)).json(&payload).send().await;
+	) -> SdkResult<String> {
+		let (handler, path) = fill_path!(ApiHandler::PayOnchain, user_id, is_fee_incl)?;
+		let req = self.request(handler, &path).json(&payload);
 
-		Sdk::process_response::<String>(resp).await
+		self.execute::<String>(handler, req).await
 	}
 
-	/// Notify FliQ Payer.
-	pub async fn fliq_notify_payer(
+	/// Pay onchain with an idempotency key (sent as `Idempotency-Key` header), so that the same
+	/// payment submitted more than once (e.g. retried after a network timeout) is processed only
+	/// once.
+	///
+	/// The request is retried on transient failures as per the SDK's retry policy.
+	///
+	/// ## Arguments
+	/// - `idempotency_key`: Use [`PayOnchainPayload::idempotency_key`] to derive it from the
+	///   payment details.
+	///
+	/// ## Errors
+	/// - [`SdkError::DuplicatePayment`] if a payment with the same key was already submitted. This
+	///   includes an earlier attempt of this call, which went through, but whose response was lost
+	///   & hence retried. So, follow its `receipt_id` (if any) instead of treating it as failed.
+	pub async fn pay_onchain_with_idempotency_key(
 		&self,
-		pid: &str,
+		user_id: &str,
+		is_fee_incl: bool,
 		payload: PayOnchainPayload,
-	) -> eyre::Result<()> {
+		idempotency_key: &str,
+	) -> SdkResult<String> {
+		if idempotency_key.trim().is_empty() || idempotency_key.len() > 255 {
+			return Err(SdkError::InvalidInput(
+				"Idempotency key must be 1 to 255 characters long".to_owned(),
+			));
+		}
+
+		let (handler, path) = fill_path!(ApiHandler::PayOnchain, user_id, is_fee_incl)?;
+		let req = self
+			.request(handler, &path)
+			.header(IDEMPOTENCY_KEY_HEADER, idempotency_key)
+			.json(&payload);
+
+		self.execute::<String>(handler, req).await.map_err(|err| match err {
+			SdkError::Conflict { body } => SdkError::DuplicatePayment {
+				idempotency_key: idempotency_key.to_owned(),
+				receipt_id: body.receipt_id(),
+				body,
+			},
+			err => err,
+		})
+	}
+
+	/// Notify FliQ Payer.
+	pub async fn fliq_notify_payer(&self, pid: &str, payload: PayOnchainPayload) -> SdkResult<()> {
 		let PayOnchainPayload { chain, coin, to_address, amount, .. } = payload;
-		let handler = ApiHandler::FliqNotifyPayer;
-		let path = handler.fill_path_ordered(&[
-			pid.to_owned(),
-			chain.to_string(),
-			coin.to_string(),
-			to_address,
-			amount,
-		])?;
-		let url = format!("{}{}", self.api_base_url, path);
-		let resp = self.with_auth(self.client.get(url)).send().await;
-
-		Sdk::process_response::<()>(resp).await
+		let (handler, path) =
+			fill_path!(ApiHandler::FliqNotifyPayer, pid, chain, coin, to_address, amount)?;
+		let req = self.request(handler, &path);
+
+		self.execute::<()>(handler, req).await
 	}
 
 	/// View onchain payment receipt
-	pub async fn get_ocp_receipt(&self, receipt_id: &str) -> eyre::Result<OcPayReceipt> {
-		let handler = ApiHandler::GetOcpReceipt;
-		let path = handler.fill_path_ordered(&[receipt_id.to_owned()])?;
-		let url = format!("{}{}", self.api_base_url, path);
-		let resp = self.with_auth(self.client.get(url)).send().await;
+	pub async fn get_ocp_receipt(&self, receipt_id: &str) -> SdkResult<OcPayReceipt> {
+		let (handler, path) = fill_path!(ApiHandler::GetOcpReceipt, receipt_id)?;
+		let req = self.request(handler, &path);
 
-		Sdk::process_response::<OcPayReceipt>(resp).await
+		self.execute::<OcPayReceipt>(handler, req).await
 	}
 
 	/// View onchain payment receipts for a user_id
+	///
+	/// NOTE: Never retried, as the server moves the user's page cursor. See
+	/// [`ApiHandler::is_idempotent`].
 	pub async fn get_ocp_receipts(
 		&self,
 		user_id: &str,
 		sort_by_latest: bool,
 		from_start: bool,
 		filter: Option<PayHistoryFilterParams>,
-	) -> eyre::Result<OcPayHistory> {
+	) -> SdkResult<OcPayHistory> {
 		let handler = ApiHandler::GetOcpReceipts;
-		let path = handler.fill_path_ordered(&[
-			user_id.to_owned(),
-			sort_by_latest.to_string(),
-			from_start.to_string(),
-		])?;
-		let mut url = format!("{}{}", self.api_base_url, path);
+		let req = self.ocp_receipts_request(user_id, sort_by_latest, from_start, filter)?;
 
-		// Add filters
-		if let Some(filter) = filter {
-			let PayHistoryFilterParams { chain, status, limit, next_or_previous } = filter;
+		self.execute::<OcPayHistory>(handler, req).await
+	}
 
-			url.push('?');
+	fn ocp_receipts_request(
+		&self,
+		user_id: &str,
+		sort_by_latest: bool,
+		from_start: bool,
+		filter: Option<PayHistoryFilterParams>,
+	) -> SdkResult<RequestBuilder> {
+		let (handler, path) =
+			fill_path!(ApiHandler::GetOcpReceipts, user_id, sort_by_latest, from_start)?;
+		let req = self.request(handler, &path);
 
-			if let Some(chain) = chain {
-				url.push_str(&format!("chain={}&", chain));
-			}
+		// Add filters (unset ones are skipped)
+		Ok(match filter {
+			Some(filter) => req.query(&filter),
+			None => req,
+		})
+	}
 
-			if let Some(status) = status {
-				url.push_str(&format!("status={}&", status));
+	/// Wait for the onchain payment receipt to reach the `until` status (or beyond) by polling
+	/// [`Sdk::get_ocp_receipt`].
+	///
+	/// E.g. `until: Confirmed` returns as soon as the payment is either `Confirmed` or
+	/// `Finalized`.
+	///
+	/// ## Errors
+	/// - [`SdkError::PaymentFailed`] / [`SdkError::PaymentReorged`]: returned early as the payment
+	///   would never reach the status.
+	/// - [`SdkError::WaitTimeout`]: status not reached within `opts.timeout`, incl. when a fetch
+	///   (with its retries) is still in flight at the deadline.
+	/// - [`SdkError::Cancelled`]: `opts.cancel` is cancelled.
+	pub async fn wait_for_receipt(
+		&self,
+		receipt_id: &str,
+		until: OcPayReceiptStatus,
+		opts: WaitForReceiptOpts,
+	) -> SdkResult<OcPayReceipt> {
+		let WaitForReceiptOpts { poll_interval, timeout, cancel } = opts;
+		let deadline = tokio::time::Instant::now() + timeout;
+		let cancel = cancel.unwrap_or_default();
+		let timed_out =
+			|last_status| SdkError::WaitTimeout { receipt_id: receipt_id.to_owned(), last_status };
+		// Until the 1st fetch succeeds.
+		let mut last_status = OcPayReceiptStatus::default();
+
+		loop {
+			// Bound the fetch (incl. its retries) too, not just the sleep.
+			let fetch = tokio::time::timeout_at(deadline, self.get_ocp_receipt(receipt_id));
+			let receipt = tokio::select! {
+				receipt = fetch => receipt.map_err(|_| timed_out(last_status))??,
+				_ = cancel.cancelled() => return Err(SdkError::Cancelled),
+			};
+			let status = receipt.status;
+			last_status = status;
+
+			if status.has_reached(until) {
+				return Ok(receipt);
+			}
+			if status.is_failed() {
+				return Err(SdkError::PaymentFailed(Box::new(receipt)));
+			}
+			if status.is_reorged() {
+				return Err(SdkError::PaymentReorged(Box::new(receipt)));
 			}
 
-			if let Some(limit) = limit {
-				url.push_str(&format!("limit={}&", limit));
+			let now = tokio::time::Instant::now();
+			if now >= deadline {
+				return Err(timed_out(status));
 			}
 
-			if let Some(next_or_previous) = next_or_previous {
-				url.push_str(&format!("next_or_previous={}&", next_or_previous));
+			tokio::select! {
+				_ = tokio::time::sleep(poll_interval.min(deadline - now)) => {},
+				_ = cancel.cancelled() => return Err(SdkError::Cancelled),
 			}
 		}
+	}
+
+	/// Stream all the onchain payment receipts (latest first) of a user, following the pages
+	/// automatically until there is no next page.
+	///
+	/// `filter.limit` is used as the page size. `filter.next_or_previous` is ignored as the
+	/// stream always moves to the next page.
+	///
+	/// ## Errors
+	/// A failed page ends the stream with the error. The pages aren't retried, as the server may
+	/// have moved the cursor anyway, which would skip a page. So, on error, restart the stream.
+	pub fn ocp_receipts_stream(
+		&self,
+		user_id: &str,
+		filter: PayHistoryFilterParams,
+	) -> impl Stream<Item = SdkResult<OcPayReceipt>> + '_ {
+		let user_id = user_id.to_owned();
+
+		// state: `Some(from_start)` => fetch the page, `None` => no more pages.
+		stream::try_unfold(Some(true), move |state| {
+			let user_id = user_id.clone();
+			async move {
+				let Some(from_start) = state else {
+					return Ok(None);
+				};
+
+				let page_filter = PayHistoryFilterParams {
+					// page-1 is loaded from start
+					next_or_previous: (!from_start).then_some(true),
+					..filter
+				};
+				let OcPayHistory { receipts, has_next, .. } =
+					self.get_ocp_receipts(&user_id, true, from_start, Some(page_filter)).await?;
 
-		let resp = self.with_auth(self.client.get(url)).send().await;
+				// NOTE: an empty page also ends the stream to avoid looping forever.
+				let next_state = (has_next && !receipts.is_empty()).then_some(false);
+				SdkResult::Ok(Some((receipts, next_state)))
+			}
+		})
+		.map_ok(|receipts| stream::iter(receipts.into_iter().map(Ok)))
+		.try_flatten()
+	}
+
+	/// Collect all the onchain payment receipts of a user via [`Sdk::ocp_receipts_stream`].
+	///
+	/// Stops after `max_items` receipts, so that a big history doesn't end up in memory.
+	///
+	/// ## Returns
+	/// (receipts, is_truncated) where `is_truncated` is true if the history has more than
+	/// `max_items` receipts i.e. the receipts aren't the complete history.
+	pub async fn collect_all_ocp_receipts(
+		&self,
+		user_id: &str,
+		filter: PayHistoryFilterParams,
+		max_items: usize,
+	) -> SdkResult<(Vec<OcPayReceipt>, bool)> {
+		// 1 more to know if there are more.
+		let mut receipts = self
+			.ocp_receipts_stream(user_id, filter)
+			.take(max_items.saturating_add(1))
+			.try_collect::<Vec<_>>()
+			.await?;
+
+		let is_truncated = receipts.len() > max_items;
+		receipts.truncate(max_items);
+		Ok((receipts, is_truncated))
+	}
+}
+
+/// Test
+/// ```sh
+/// RUSTFLAGS="-Awarnings" cargo t -p unifi-sdk-rs -- pay::tests --show-output
+/// ```
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn receipts_url(filter: Option<PayHistoryFilterParams>) -> String {
+		let sdk = Sdk::new("https://api.unifi.io/", "key");
+		let req = sdk.ocp_receipts_request("user 1", true, false, filter).unwrap();
+		req.build().unwrap().url().to_string()
+	}
 
-		Sdk::process_response::<OcPayHistory>(resp).await
+	#[test]
+	fn builds_receipts_query() {
+		assert_eq!(
+			receipts_url(None),
+			"https://api.unifi.io/payment/onchain/receipts/user%201/true/false"
+		);
+		assert_eq!(
+			receipts_url(Some(PayHistoryFilterParams::default())),
+			"https://api.unifi.io/payment/onchain/receipts/user%201/true/false"
+		);
+		assert_eq!(
+			receipts_url(Some(PayHistoryFilterParams {
+				chain: Some(ChainName::Sepolia),
+				status: Some(OcPayReceiptStatus::Confirmed),
+				limit: Some(20),
+				next_or_previous: Some(true),
+			})),
+			"https://api.unifi.io/payment/onchain/receipts/user%201/true/false?chain=Sepolia&status=Confirmed&limit=20&next_or_previous=true"
+		);
+		assert_eq!(
+			receipts_url(Some(PayHistoryFilterParams { limit: Some(5), ..Default::default() })),
+			"https://api.unifi.io/payment/onchain/receipts/user%201/true/false?limit=5"
+		);
 	}
 }
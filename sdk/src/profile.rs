@@ -1,27 +1,53 @@
 use super::Sdk;
-use crate::types::ApiHandler;
-use unifi_sdk_primitives::types::UserProfile;
+use crate::{
+	errors::SdkResult,
+	types::{ApiHandler, fill_path},
+};
+use unifi_sdk_primitives::types::{Pid, Uid, UserProfile};
 
 impl Sdk {
 	pub async fn set_user_profile(
 		&self,
 		user_id: &str,
 		user_profile: &UserProfile,
-	) -> eyre::Result<()> {
-		let handler = ApiHandler::SetUserProfile;
-		let path = handler.fill_path_ordered(&[user_id.to_owned()])?;
-		let url = format!("{}{}", self.api_base_url, path);
-		let resp = self.with_auth(self.client.post(url)).json(&user_profile).send().await;
+	) -> SdkResult<()> {
+		let (handler, path) = fill_path!(ApiHandler::SetUserProfile, user_id)?;
+		let req = self.request(handler, &path).json(&user_profile);
 
-		Sdk::process_response::<()>(resp).await
+		self.execute::<()>(handler, req).await
 	}
 
-	pub async fn get_user_profile(&self, user_id: &str) -> eyre::Result<UserProfile> {
-		let handler = ApiHandler::GetUserProfile;
-		let path = handler.fill_path_ordered(&[user_id.to_owned()])?;
-		let url = format!("{}{}", self.api_base_url, path);
-		let resp = self.with_auth(self.client.get(url)).send().await;
+	pub async fn get_user_profile(&self, user_id: &str) -> SdkResult<UserProfile> {
+		let (handler, path) = fill_path!(ApiHandler::GetUserProfile, user_id)?;
+		let req = self.request(handler, &path);
 
-		Sdk::process_response::<UserProfile>(resp).await
+		self.execute::<UserProfile>(handler, req).await
+	}
+
+	/// Get the user's UID.
+	pub async fn get_uid(&self, user_id: &str) -> SdkResult<Uid> {
+		let (handler, path) = fill_path!(ApiHandler::GetUserUid, user_id)?;
+		let req = self.request(handler, &path);
+
+		self.execute::<Uid>(handler, req).await
+	}
+
+	/// Get the user's PID to be shared with payment requesters.
+	pub async fn get_pid(&self, user_id: &str) -> SdkResult<Pid> {
+		let (handler, path) = fill_path!(ApiHandler::GetUserPid, user_id)?;
+		let req = self.request(handler, &path);
+
+		self.execute::<Pid>(handler, req).await
+	}
+
+	/// Generate a new PID for the user e.g. on unwanted payment notifications. The old PID stops
+	/// working, so share the new one with the preferred payment requesters.
+	///
+	/// NOTE: Not retried on failure as the PID might have been rotated.
+	pub async fn rotate_pid(&self, user_id: &str) -> SdkResult<Pid> {
+		let (handler, path) = fill_path!(ApiHandler::RotateUserPid, user_id)?;
+		let req = self.request(handler, &path);
+
+		self.execute::<Pid>(handler, req).await
 	}
 }
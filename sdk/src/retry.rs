@@ -0,0 +1,173 @@
+use crate::{errors::SdkError, types::ApiHandler};
+use reqwest::Request;
+use std::time::Duration;
+
+/// Header carrying the deduplication token of a non-idempotent request.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Retry policy for transient failures i.e. `429` & `5xx` responses, timeouts and connection
+/// errors.
+///
+/// By default, only idempotent handlers (see [`ApiHandler::is_idempotent`]) are retried. A
+/// non-idempotent one (e.g. `PayOnchain`) is retried only if the request carries an
+/// [`IDEMPOTENCY_KEY_HEADER`], so that the server can deduplicate it.
+///
+/// ## Backoff
+/// ```text
+/// delay = min(max_delay, base_delay * 2^(attempt - 1))
+/// ```
+/// With `jitter`, a random delay in `[delay / 2, delay]` is used instead to avoid retry storms.
+/// If the server sends `Retry-After` (on `429`), that delay is used instead, as long as it isn't
+/// longer than `max_delay`. Else, the error is returned right away.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+	/// Max. no. of attempts including the first one. `1` means no retry.
+	pub max_attempts: u32,
+	pub base_delay: Duration,
+	pub max_delay: Duration,
+	pub jitter: bool,
+	pub respect_retry_after: bool,
+	/// Retry non-idempotent handlers if the request has an idempotency key.
+	pub retry_with_idempotency_key: bool,
+}
+
+impl Default for RetryPolicy {
+	fn default() -> Self {
+		Self {
+			max_attempts: 3,
+			base_delay: Duration::from_millis(500),
+			max_delay: Duration::from_secs(30),
+			jitter: true,
+			respect_retry_after: true,
+			retry_with_idempotency_key: true,
+		}
+	}
+}
+
+impl RetryPolicy {
+	/// No retry at all.
+	pub fn none() -> Self {
+		Self { max_attempts: 1, ..Default::default() }
+	}
+
+	/// Can the request for the handler be sent again?
+	pub(crate) fn allows(&self, handler: ApiHandler, request: &Request) -> bool {
+		self.max_attempts > 1 &&
+			(handler.is_idempotent() ||
+				(self.retry_with_idempotency_key &&
+					request.headers().contains_key(IDEMPOTENCY_KEY_HEADER)))
+	}
+
+	/// Delay before the next attempt, given the failed `attempt` (starting from 1) & its error.
+	///
+	/// `None` => don't retry.
+	pub(crate) fn next_delay(&self, attempt: u32, err: &SdkError) -> Option<Duration> {
+		if attempt >= self.max_attempts || !err.is_retryable() {
+			return None;
+		}
+
+		if let SdkError::RateLimited { retry_after: Some(retry_after), .. } = err &&
+			self.respect_retry_after
+		{
+			return (*retry_after <= self.max_delay).then_some(*retry_after);
+		}
+
+		let delay = self
+			.base_delay
+			.saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+			.min(self.max_delay);
+
+		if self.jitter {
+			let half = delay / 2;
+			Some(half + half.mul_f64(fastrand::f64()))
+		} else {
+			Some(delay)
+		}
+	}
+}
+
+/// Test
+/// ```sh
+/// RUSTFLAGS="-Awarnings" cargo t -p unifi-sdk-rs -- retry::tests --show-output
+/// ```
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::errors::ApiErrorBody;
+	use reqwest::{Method, Url};
+
+	fn server_err() -> SdkError {
+		SdkError::Server { status: 503, body: ApiErrorBody::default() }
+	}
+
+	#[test]
+	fn exponential_backoff() {
+		let policy = RetryPolicy {
+			max_attempts: 5,
+			max_delay: Duration::from_secs(2),
+			jitter: false,
+			..Default::default()
+		};
+
+		assert_eq!(policy.next_delay(1, &server_err()), Some(Duration::from_millis(500)));
+		assert_eq!(policy.next_delay(2, &server_err()), Some(Duration::from_secs(1)));
+		assert_eq!(policy.next_delay(3, &server_err()), Some(Duration::from_secs(2)));
+		assert_eq!(policy.next_delay(4, &server_err()), Some(Duration::from_secs(2)));
+		// attempts exhausted
+		assert_eq!(policy.next_delay(5, &server_err()), None);
+	}
+
+	#[test]
+	fn jitter_within_bounds() {
+		let policy = RetryPolicy::default();
+		for _ in 0..100 {
+			let delay = policy.next_delay(2, &server_err()).unwrap();
+			assert!(delay >= Duration::from_millis(500) && delay <= Duration::from_secs(1));
+		}
+	}
+
+	#[test]
+	fn honors_retry_after() {
+		let policy = RetryPolicy::default();
+		let rate_limited = |secs| SdkError::RateLimited {
+			retry_after: Some(Duration::from_secs(secs)),
+			body: ApiErrorBody::default(),
+		};
+
+		assert_eq!(policy.next_delay(1, &rate_limited(3)), Some(Duration::from_secs(3)));
+		// longer than `max_delay`
+		assert_eq!(policy.next_delay(1, &rate_limited(60)), None);
+	}
+
+	#[test]
+	fn skips_non_retryable() {
+		let policy = RetryPolicy::default();
+		let conflict = SdkError::Conflict { body: ApiErrorBody::default() };
+		let not_impl = SdkError::Server { status: 501, body: ApiErrorBody::default() };
+
+		assert_eq!(policy.next_delay(1, &conflict), None);
+		assert_eq!(policy.next_delay(1, &not_impl), None);
+	}
+
+	#[test]
+	fn allows_only_idempotent_or_keyed() {
+		let policy = RetryPolicy::default();
+		let url = Url::parse("https://api.unifi.io/payment/onchain/user/false").unwrap();
+		let mut request = Request::new(Method::POST, url);
+
+		assert!(policy.allows(ApiHandler::GetOcpReceipt, &request));
+		assert!(!policy.allows(ApiHandler::PayOnchain, &request));
+		assert!(!policy.allows(ApiHandler::FliqNotifyPayer, &request));
+		// moves the page cursor
+		assert!(!policy.allows(ApiHandler::GetOcpReceipts, &request));
+		assert!(!policy.allows(ApiHandler::RotateUserPid, &request));
+
+		request.headers_mut().insert(IDEMPOTENCY_KEY_HEADER, "key".parse().unwrap());
+		assert!(policy.allows(ApiHandler::PayOnchain, &request));
+		assert!(
+			!RetryPolicy { retry_with_idempotency_key: false, ..Default::default() }
+				.allows(ApiHandler::PayOnchain, &request)
+		);
+		assert!(!RetryPolicy::none().allows(ApiHandler::GetOcpReceipt, &request));
+	}
+}
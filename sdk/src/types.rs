@@ -1,7 +1,31 @@
-use crate::errors::OmniPayError;
+use crate::errors::{OmniPayError, SdkError, SdkResult};
+use percent_encoding::{AsciiSet, CONTROLS, utf8_percent_encode};
+use reqwest::Method;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Copy)]
+/// Chars to be percent-encoded in a path segment i.e. everything other than unreserved
+/// (RFC 3986) & a few safe sub-delims.
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+	.add(b' ')
+	.add(b'"')
+	.add(b'#')
+	.add(b'%')
+	.add(b'/')
+	.add(b'<')
+	.add(b'>')
+	.add(b'?')
+	.add(b'[')
+	.add(b'\\')
+	.add(b']')
+	.add(b'^')
+	.add(b'`')
+	.add(b'{')
+	.add(b'|')
+	.add(b'}');
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Copy)]
 #[serde(rename_all = "snake_case")]
 pub enum ApiHandler {
 	/* Health */
@@ -11,6 +35,9 @@ pub enum ApiHandler {
 	/* Profile */
 	SetUserProfile,
 	GetUserProfile,
+	GetUserUid,
+	GetUserPid,
+	RotateUserPid,
 
 	/* Contacts */
 	AddUserContacts,
@@ -42,7 +69,40 @@ pub enum ApiHandler {
 
 // #[cfg(any(feature = "types"))]
 impl ApiHandler {
-	pub fn path(&self) -> &'static str {
+	pub const fn all() -> &'static [ApiHandler] {
+		use ApiHandler as AH;
+		&[
+			AH::HealthCheck,
+			AH::Healthz,
+			AH::SetUserProfile,
+			AH::GetUserProfile,
+			AH::GetUserUid,
+			AH::GetUserPid,
+			AH::RotateUserPid,
+			AH::AddUserContacts,
+			AH::DelUserContact,
+			AH::UpdateUserContact,
+			AH::GetUserContacts,
+			AH::GetUserContactByUid,
+			AH::GetUserContactsByName,
+			AH::FetchPreOcpNetOnchainBalance,
+			AH::FetchPreOcpTotalEstFees,
+			AH::FetchPreOcpBalanceAndEstFees,
+			AH::RequestFaucet,
+			AH::PayOnchain,
+			AH::FliqNotifyPayer,
+			AH::GetOcpReceipt,
+			AH::GetOcpReceipts,
+			AH::GetUserWalletAddress,
+			AH::GetUserWalletAddresses,
+			AH::GetOcChainCoinBalance,
+			AH::GetOcChainAllCoinsBalances,
+			AH::GetWalletBalancesByChain,
+			AH::GetWalletBalancesByCoin,
+		]
+	}
+
+	pub const fn path(&self) -> &'static str {
 		use ApiHandler as AH;
 		match self {
 			/* Health */
@@ -51,6 +111,8 @@ impl ApiHandler {
 
 			/* Profile */
 			AH::SetUserProfile | AH::GetUserProfile => "/profile/{user_id}",
+			AH::GetUserUid => "/user/uid/{user_id}",
+			AH::GetUserPid | AH::RotateUserPid => "/user/pid/{user_id}",
 
 			/* Contacts */
 			AH::GetUserContacts | AH::AddUserContacts => "/contacts/{user_id}",
@@ -83,43 +145,133 @@ impl ApiHandler {
 		}
 	}
 
-	/// Replaces `{}`-wrapped placeholders in a given path using the provided parameters,
-	/// assuming the order of the parameters corresponds exactly to the order of placeholders.
+	/// HTTP method of the handler.
+	pub fn method(&self) -> Method {
+		use ApiHandler as AH;
+		match self {
+			AH::SetUserProfile |
+			AH::RotateUserPid |
+			AH::AddUserContacts |
+			AH::RequestFaucet |
+			AH::PayOnchain => Method::POST,
+			AH::UpdateUserContact => Method::PUT,
+			AH::DelUserContact => Method::DELETE,
+			AH::HealthCheck |
+			AH::Healthz |
+			AH::GetUserProfile |
+			AH::GetUserUid |
+			AH::GetUserPid |
+			AH::GetUserContacts |
+			AH::GetUserContactByUid |
+			AH::GetUserContactsByName |
+			AH::FetchPreOcpNetOnchainBalance |
+			AH::FetchPreOcpTotalEstFees |
+			AH::FetchPreOcpBalanceAndEstFees |
+			AH::FliqNotifyPayer |
+			AH::GetOcpReceipt |
+			AH::GetOcpReceipts |
+			AH::GetUserWalletAddress |
+			AH::GetUserWalletAddresses |
+			AH::GetOcChainCoinBalance |
+			AH::GetOcChainAllCoinsBalances |
+			AH::GetWalletBalancesByChain |
+			AH::GetWalletBalancesByCoin => Method::GET,
+		}
+	}
+
+	/// Is the handler safe to be sent again (e.g. on retry) without any side effect?
+	///
+	/// NOTE: Some GETs aren't idempotent:
+	/// - `FliqNotifyPayer`: each call notifies the payer.
+	/// - `GetOcpReceipts`: moves the user's page cursor (unless loaded from start). So, a resent
+	///   request (whose response was lost) would skip a page.
+	pub fn is_idempotent(&self) -> bool {
+		self.method() == Method::GET &&
+			!matches!(self, ApiHandler::FliqNotifyPayer | ApiHandler::GetOcpReceipts)
+	}
+
+	/// Does the handler's path have exactly these placeholder `names` (in any order, no
+	/// duplicates)?
+	///
+	/// NOTE: `const` so that [`fill_path!`] can check the names at compile time.
+	pub const fn has_path_params(&self, names: &[&str]) -> bool {
+		let template = self.path().as_bytes();
+
+		let mut i = 0;
+		while i < names.len() {
+			if !has_placeholder(template, names[i].as_bytes()) {
+				return false;
+			}
+			let mut j = i + 1;
+			while j < names.len() {
+				if bytes_eq(names[i].as_bytes(), names[j].as_bytes()) {
+					return false;
+				}
+				j += 1;
+			}
+			i += 1;
+		}
+
+		count_placeholders(template) == names.len()
+	}
+
+	/// Replaces `{}`-wrapped placeholders in the handler's path with the params of the same
+	/// name, irrespective of their order.
+	///
+	/// Prefer [`fill_path!`] which also checks the names at compile time.
 	///
 	/// # Arguments
-	/// - `template`: a path with placeholders (e.g. "/user/{coin}/{chain}")
-	/// - `params`: a slice of values to substitute in order
+	/// - `params`: `(name, value)` pairs e.g. `[("user_id", "abc"), ("coin", "USDT")]`
 	///
 	/// # Returns
-	/// A new path string with placeholders replaced
-	pub fn fill_path_ordered(&self, params: &[String]) -> eyre::Result<String> {
+	/// A new path string with placeholders replaced by the percent-encoded params. E.g. "Jane
+	/// Doe" is filled as "Jane%20Doe" & "a/b" as "a%2Fb".
+	///
+	/// # Errors
+	/// - If a placeholder has no param, a param has no placeholder or a param is repeated.
+	/// - [`SdkError::InvalidInput`] if a param is empty, `.` or `..`, as the URL parsing would drop
+	///   or resolve the (dot-)segment, sending the request to another endpoint.
+	pub fn fill_path<V: AsRef<str>>(&self, params: &[(&str, V)]) -> SdkResult<String> {
+		for (i, (name, value)) in params.iter().enumerate() {
+			if params[..i].iter().any(|(prev, _)| prev == name) {
+				return Err(OmniPayError::DuplicateParamForApiPath(name.to_string()).into());
+			}
+			if matches!(value.as_ref(), "" | "." | "..") {
+				return Err(SdkError::InvalidInput(format!(
+					"Path param `{}` can't be empty, `.` or `..`: {:?}",
+					name,
+					value.as_ref()
+				)));
+			}
+		}
+
 		let template = self.path();
 		let mut filled_path = String::new();
+		let mut placeholders = vec![];
 		let mut i = 0;
-		let mut param_index = 0;
 
 		while let Some(start) = template[i..].find('{') {
 			let abs_start = i + start;
-			if let Some(end) = template[abs_start..].find('}') {
-				let abs_end = abs_start + end;
-				// Push text before placeholder
-				filled_path.push_str(&template[i..abs_start]);
-				// Push replacement value
-				if let Some(val) = params.get(param_index) {
-					filled_path.push_str(val);
-					param_index += 1;
-				} else {
-					return Err(OmniPayError::LessParamsForApiPath.into());
-				}
-				i = abs_end + 1;
-			} else {
+			let Some(end) = template[abs_start..].find('}') else {
 				return Err(OmniPayError::UnclosedPlaceholderInApiPathTemplate.into());
-			}
+			};
+			let abs_end = abs_start + end;
+			let name = &template[abs_start + 1..abs_end];
+
+			// Push text before placeholder
+			filled_path.push_str(&template[i..abs_start]);
+			// Push replacement value
+			let Some((_, val)) = params.iter().find(|(param, _)| *param == name) else {
+				return Err(OmniPayError::MissingParamForApiPath(name.to_owned()).into());
+			};
+			filled_path.extend(utf8_percent_encode(val.as_ref(), PATH_SEGMENT));
+			placeholders.push(name);
+			i = abs_end + 1;
 		}
 
 		// Ensure there are no extra params
-		if param_index != params.len() {
-			return Err(OmniPayError::MoreParamsForApiPath.into());
+		if let Some((name, _)) = params.iter().find(|(name, _)| !placeholders.contains(name)) {
+			return Err(OmniPayError::UnknownParamForApiPath(name.to_string()).into());
 		}
 
 		// Push remaining part
@@ -127,3 +279,283 @@ impl ApiHandler {
 		Ok(filled_path)
 	}
 }
+
+/// Fill the path of an [`ApiHandler`] variant with named params, checked at compile time
+/// against the path's placeholders. A param is either `name = value` or just `name` (when a
+/// variable has the same name). Values are formatted via `ToString`.
+///
+/// ## Returns
+/// The handler along with its filled path, so that the variant is named only once.
+///
+/// ```ignore
+/// let (handler, path) =
+///     fill_path!(ApiHandler::RequestFaucet, user_id, coin, chain = ChainName::Sepolia)?;
+/// // "/faucet/{user_id}/{coin}/{chain}" => "/faucet/abc/USDT/Sepolia"
+/// ```
+///
+/// A misspelt, missing or extra name fails the build:
+/// ```ignore
+/// // error: Params don't match the path placeholders of ApiHandler::RequestFaucet
+/// let (handler, path) = fill_path!(ApiHandler::RequestFaucet, user_id, coin)?;
+/// ```
+macro_rules! fill_path {
+	(@value $name:ident = $value:expr) => {
+		$value
+	};
+	(@value $name:ident) => {
+		$name
+	};
+	($handler:path, $($name:ident $(= $value:expr)?),+ $(,)?) => {{
+		const _: () = assert!(
+			$handler.has_path_params(&[$(stringify!($name)),+]),
+			concat!("Params don't match the path placeholders of ", stringify!($handler))
+		);
+		$handler
+			.fill_path(&[
+				$((stringify!($name), fill_path!(@value $name $(= $value)?).to_string())),+
+			])
+			.map(|path| ($handler, path))
+	}};
+}
+pub(crate) use fill_path;
+
+/// Is `{name}` in the template?
+const fn has_placeholder(template: &[u8], name: &[u8]) -> bool {
+	let mut i = 0;
+	while i + name.len() + 1 < template.len() {
+		if template[i] == b'{' && template[i + name.len() + 1] == b'}' {
+			let mut j = 0;
+			while j < name.len() && template[i + 1 + j] == name[j] {
+				j += 1;
+			}
+			if j == name.len() {
+				return true;
+			}
+		}
+		i += 1;
+	}
+	false
+}
+
+const fn count_placeholders(template: &[u8]) -> usize {
+	let mut count = 0;
+	let mut i = 0;
+	while i < template.len() {
+		if template[i] == b'{' {
+			count += 1;
+		}
+		i += 1;
+	}
+	count
+}
+
+const fn bytes_eq(a: &[u8], b: &[u8]) -> bool {
+	if a.len() != b.len() {
+		return false;
+	}
+	let mut i = 0;
+	while i < a.len() {
+		if a[i] != b[i] {
+			return false;
+		}
+		i += 1;
+	}
+	true
+}
+
+/// Options for [`Sdk::wait_for_receipt`](crate::Sdk::wait_for_receipt).
+#[derive(Debug, Clone)]
+pub struct WaitForReceiptOpts {
+	/// Interval between 2 consecutive `get_ocp_receipt` calls.
+	pub poll_interval: Duration,
+	/// Max. time to wait for the receipt to reach the status.
+	pub timeout: Duration,
+	/// Cancel the wait (e.g. on shutdown).
+	pub cancel: Option<CancellationToken>,
+}
+
+impl Default for WaitForReceiptOpts {
+	fn default() -> Self {
+		Self {
+			poll_interval: Duration::from_secs(3),
+			timeout: Duration::from_secs(300),
+			cancel: None,
+		}
+	}
+}
+
+/// Test
+/// ```sh
+/// RUSTFLAGS="-Awarnings" cargo t -p unifi-sdk-rs -- types::tests --show-output
+/// ```
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use unifi_sdk_primitives::types::{ChainName, StableCoin};
+
+	#[test]
+	fn fills_path_by_name() {
+		assert_eq!(
+			ApiHandler::GetOcChainCoinBalance
+				.fill_path(&[("coin", "USDT"), ("user_id", "user1"), ("chain", "Sepolia")])
+				.unwrap(),
+			"/wallet/onchain/balance/user1/Sepolia/USDT"
+		);
+		// faucet is `{coin}/{chain}` unlike the balance endpoints.
+		let (user_id, coin, chain) = ("user1", StableCoin::USDT, ChainName::Sepolia);
+		assert_eq!(
+			fill_path!(ApiHandler::RequestFaucet, user_id, chain, coin).unwrap(),
+			(ApiHandler::RequestFaucet, "/faucet/user1/USDT/Sepolia".to_owned())
+		);
+		assert_eq!(
+			fill_path!(ApiHandler::PayOnchain, user_id = "user1", is_fee_incl = false)
+				.unwrap()
+				.1,
+			"/payment/onchain/user1/false"
+		);
+	}
+
+	#[test]
+	fn rejects_mismatched_params() {
+		assert!(matches!(
+			ApiHandler::GetUserContactByUid.fill_path(&[("user_id", "user1")]),
+			Err(SdkError::Request(OmniPayError::MissingParamForApiPath(name))) if name == "uid"
+		));
+		assert!(matches!(
+			ApiHandler::GetUserContacts.fill_path(&[("user_id", "user1"), ("uid", "x")]),
+			Err(SdkError::Request(OmniPayError::UnknownParamForApiPath(name))) if name == "uid"
+		));
+		assert!(matches!(
+			ApiHandler::GetUserContacts.fill_path(&[("user_id", "user1"), ("user_id", "x")]),
+			Err(SdkError::Request(OmniPayError::DuplicateParamForApiPath(name))) if name == "user_id"
+		));
+	}
+
+	#[test]
+	fn checks_path_params() {
+		let handler = ApiHandler::RequestFaucet;
+		assert!(handler.has_path_params(&["user_id", "coin", "chain"]));
+		assert!(handler.has_path_params(&["chain", "coin", "user_id"]));
+		assert!(!handler.has_path_params(&["user_id", "coin"]));
+		assert!(!handler.has_path_params(&["user_id", "coin", "chain", "amount"]));
+		assert!(!handler.has_path_params(&["user_id", "coin", "coin"]));
+		assert!(!handler.has_path_params(&["user", "coin", "chain"]));
+		assert!(ApiHandler::Healthz.has_path_params(&[]));
+	}
+
+	#[test]
+	fn encodes_path_params() {
+		let fill = |name: &str| {
+			ApiHandler::GetUserContactsByName
+				.fill_path(&[("user_id", "user1"), ("name", name)])
+				.unwrap()
+		};
+
+		assert_eq!(fill("Jane Doe"), "/contacts/by_name/user1/Jane%20Doe");
+		assert_eq!(fill("a/b"), "/contacts/by_name/user1/a%2Fb");
+		assert_eq!(fill("rent?x=1#y"), "/contacts/by_name/user1/rent%3Fx=1%23y");
+		assert_eq!(fill("100%"), "/contacts/by_name/user1/100%25");
+		assert_eq!(fill("José"), "/contacts/by_name/user1/Jos%C3%A9");
+		assert_eq!(fill("{uid}"), "/contacts/by_name/user1/%7Buid%7D");
+		// unreserved chars kept as is
+		assert_eq!(fill("a-b_c.d~e"), "/contacts/by_name/user1/a-b_c.d~e");
+		// dots are fine within a segment
+		assert_eq!(fill("..."), "/contacts/by_name/user1/...");
+		// `%` is encoded, so can't form an encoded dot-segment
+		assert_eq!(fill("%2E%2E"), "/contacts/by_name/user1/%252E%252E");
+	}
+
+	#[test]
+	fn rejects_dot_segments() {
+		let fill =
+			|uid: &str| ApiHandler::DelUserContact.fill_path(&[("user_id", "user1"), ("uid", uid)]);
+
+		// would resolve to e.g. "/contacts/user1" or "/contacts"
+		assert!(matches!(fill(""), Err(SdkError::InvalidInput(msg)) if msg.contains("`uid`")));
+		assert!(matches!(fill("."), Err(SdkError::InvalidInput(_))));
+		assert!(matches!(fill(".."), Err(SdkError::InvalidInput(_))));
+		assert!(matches!(
+			ApiHandler::GetUserContacts.fill_path(&[("user_id", "..")]),
+			Err(SdkError::InvalidInput(_))
+		));
+		assert!(fill(".hidden").is_ok());
+	}
+
+	/// Every variant, matched exhaustively so that a new one fails the build until listed here.
+	fn every_handler() -> Vec<ApiHandler> {
+		use ApiHandler as AH;
+		let listed = |handler: AH| match handler {
+			AH::HealthCheck |
+			AH::Healthz |
+			AH::SetUserProfile |
+			AH::GetUserProfile |
+			AH::GetUserUid |
+			AH::GetUserPid |
+			AH::RotateUserPid |
+			AH::AddUserContacts |
+			AH::DelUserContact |
+			AH::UpdateUserContact |
+			AH::GetUserContacts |
+			AH::GetUserContactByUid |
+			AH::GetUserContactsByName |
+			AH::FetchPreOcpNetOnchainBalance |
+			AH::FetchPreOcpTotalEstFees |
+			AH::FetchPreOcpBalanceAndEstFees |
+			AH::RequestFaucet |
+			AH::PayOnchain |
+			AH::FliqNotifyPayer |
+			AH::GetOcpReceipt |
+			AH::GetOcpReceipts |
+			AH::GetUserWalletAddress |
+			AH::GetUserWalletAddresses |
+			AH::GetOcChainCoinBalance |
+			AH::GetOcChainAllCoinsBalances |
+			AH::GetWalletBalancesByChain |
+			AH::GetWalletBalancesByCoin => handler,
+		};
+		[
+			AH::HealthCheck,
+			AH::Healthz,
+			AH::SetUserProfile,
+			AH::GetUserProfile,
+			AH::GetUserUid,
+			AH::GetUserPid,
+			AH::RotateUserPid,
+			AH::AddUserContacts,
+			AH::DelUserContact,
+			AH::UpdateUserContact,
+			AH::GetUserContacts,
+			AH::GetUserContactByUid,
+			AH::GetUserContactsByName,
+			AH::FetchPreOcpNetOnchainBalance,
+			AH::FetchPreOcpTotalEstFees,
+			AH::FetchPreOcpBalanceAndEstFees,
+			AH::RequestFaucet,
+			AH::PayOnchain,
+			AH::FliqNotifyPayer,
+			AH::GetOcpReceipt,
+			AH::GetOcpReceipts,
+			AH::GetUserWalletAddress,
+			AH::GetUserWalletAddresses,
+			AH::GetOcChainCoinBalance,
+			AH::GetOcChainAllCoinsBalances,
+			AH::GetWalletBalancesByChain,
+			AH::GetWalletBalancesByCoin,
+		]
+		.map(listed)
+		.to_vec()
+	}
+
+	#[test]
+	fn handlers_are_exhaustive() {
+		let handlers = every_handler();
+		assert_eq!(ApiHandler::all().len(), handlers.len());
+		for handler in handlers {
+			assert!(
+				ApiHandler::all().contains(&handler),
+				"{:?} not in `ApiHandler::all()`",
+				handler
+			);
+		}
+	}
+}
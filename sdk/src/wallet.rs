@@ -1,5 +1,8 @@
 use super::Sdk;
-use crate::types::ApiHandler;
+use crate::{
+	errors::SdkResult,
+	types::{ApiHandler, fill_path},
+};
 use std::collections::HashMap;
 use unifi_sdk_primitives::{
 	types::{ChainName, ChainProtocol, StableCoin, WalletBalancesByChain, WalletBalancesByCoin},
@@ -11,32 +14,28 @@ impl Sdk {
 		&self,
 		user_id: &str,
 		chain: ChainName,
-	) -> eyre::Result<String> {
+	) -> SdkResult<String> {
 		if sanitize_address(user_id) {
 			return Ok(user_id.to_owned())
 		}
 
-		let handler = ApiHandler::GetUserWalletAddress;
-		let path = handler.fill_path_ordered(&[user_id.to_owned(), chain.to_string()])?;
-		let url = format!("{}{}", self.api_base_url, path);
-		let resp = self.with_auth(self.client.get(url)).send().await;
-		Sdk::process_response::<String>(resp).await
+		let (handler, path) = fill_path!(ApiHandler::GetUserWalletAddress, user_id, chain)?;
+		let req = self.request(handler, &path);
+		self.execute::<String>(handler, req).await
 	}
 
 	pub async fn get_user_wallet_addresses(
 		&self,
 		user_id: &str,
-	) -> eyre::Result<HashMap<ChainProtocol, String>> {
+	) -> SdkResult<HashMap<ChainProtocol, String>> {
 		if sanitize_address(user_id) {
 			// WARN: only EVM protocol
 			return Ok(HashMap::from([(ChainProtocol::Evm, user_id.to_owned())]));
 		}
 
-		let handler = ApiHandler::GetUserWalletAddresses;
-		let path = handler.fill_path_ordered(&[user_id.to_owned()])?;
-		let url = format!("{}{}", self.api_base_url, path);
-		let resp = self.with_auth(self.client.get(url)).send().await;
-		Sdk::process_response::<HashMap<ChainProtocol, String>>(resp).await
+		let (handler, path) = fill_path!(ApiHandler::GetUserWalletAddresses, user_id)?;
+		let req = self.request(handler, &path);
+		self.execute::<HashMap<ChainProtocol, String>>(handler, req).await
 	}
 
 	/// Get a chain's coin **net** balance for a given user
@@ -45,17 +44,11 @@ impl Sdk {
 		user_id: &str,
 		chain: ChainName,
 		coin: StableCoin,
-	) -> eyre::Result<String> {
-		let handler = ApiHandler::GetOcChainCoinBalance;
-		let path = handler.fill_path_ordered(&[
-			user_id.to_owned(),
-			chain.to_string(),
-			coin.to_string(),
-		])?;
-		let url = format!("{}{}", self.api_base_url, path);
-		let resp = self.with_auth(self.client.get(url)).send().await;
+	) -> SdkResult<String> {
+		let (handler, path) = fill_path!(ApiHandler::GetOcChainCoinBalance, user_id, chain, coin)?;
+		let req = self.request(handler, &path);
 
-		Sdk::process_response::<String>(resp).await
+		self.execute::<String>(handler, req).await
 	}
 
 	/// Get a chain's all coins balances for a given user
@@ -63,13 +56,11 @@ impl Sdk {
 		&self,
 		user_id: &str,
 		chain: ChainName,
-	) -> eyre::Result<HashMap<StableCoin, String>> {
-		let handler = ApiHandler::GetOcChainAllCoinsBalances;
-		let path = handler.fill_path_ordered(&[user_id.to_owned(), chain.to_string()])?;
-		let url = format!("{}{}", self.api_base_url, path);
-		let resp = self.with_auth(self.client.get(url)).send().await;
+	) -> SdkResult<HashMap<StableCoin, String>> {
+		let (handler, path) = fill_path!(ApiHandler::GetOcChainAllCoinsBalances, user_id, chain)?;
+		let req = self.request(handler, &path);
 
-		Sdk::process_response::<HashMap<StableCoin, String>>(resp).await
+		self.execute::<HashMap<StableCoin, String>>(handler, req).await
 	}
 
 	/// Get user's wallet balances on a chain
@@ -80,13 +71,11 @@ impl Sdk {
 		&self,
 		user_id: &str,
 		chain: ChainName,
-	) -> eyre::Result<WalletBalancesByChain> {
-		let handler = ApiHandler::GetWalletBalancesByChain;
-		let path = handler.fill_path_ordered(&[user_id.to_owned(), chain.to_string()])?;
-		let url = format!("{}{}", self.api_base_url, path);
-		let resp = self.with_auth(self.client.get(url)).send().await;
+	) -> SdkResult<WalletBalancesByChain> {
+		let (handler, path) = fill_path!(ApiHandler::GetWalletBalancesByChain, user_id, chain)?;
+		let req = self.request(handler, &path);
 
-		Sdk::process_response::<WalletBalancesByChain>(resp).await
+		self.execute::<WalletBalancesByChain>(handler, req).await
 	}
 
 	/// Get user's wallet balances for a coin
@@ -97,12 +86,10 @@ impl Sdk {
 		&self,
 		user_id: &str,
 		coin: StableCoin,
-	) -> eyre::Result<WalletBalancesByCoin> {
-		let handler = ApiHandler::GetWalletBalancesByCoin;
-		let path = handler.fill_path_ordered(&[user_id.to_owned(), coin.to_string()])?;
-		let url = format!("{}{}", self.api_base_url, path);
-		let resp = self.with_auth(self.client.get(url)).send().await;
+	) -> SdkResult<WalletBalancesByCoin> {
+		let (handler, path) = fill_path!(ApiHandler::GetWalletBalancesByCoin, user_id, coin)?;
+		let req = self.request(handler, &path);
 
-		Sdk::process_response::<WalletBalancesByCoin>(resp).await
+		self.execute::<WalletBalancesByCoin>(handler, req).await
 	}
 }